@@ -1,17 +1,20 @@
-use std::io;
-use std::io::prelude::*;
+use aoc::{get_lines, Answer, Solution};
 
-fn apply_frequency_changes(starting:i32, elements:Vec<String>) -> i32 {
+fn parse_change(el:&str) -> Result<i32, String> {
+    el.parse::<i32>().map_err(|_| format!("invalid frequency change {:?}", el))
+}
+
+fn apply_frequency_changes(starting:i32, elements:Vec<String>) -> Result<i32, String> {
     let mut result_frequency:i32 = starting;
 
     for el in elements {
-        result_frequency = apply_frequency_change(result_frequency, el.parse::<i32>().unwrap())
+        result_frequency = apply_frequency_change(result_frequency, parse_change(&el)?)
     }
 
-    return result_frequency;
+    return Ok(result_frequency);
 }
 
-fn apply_frequency_changes_and_find_repeat(starting:i32, elements:Vec<String>) -> i32 {
+fn apply_frequency_changes_and_find_repeat(starting:i32, elements:Vec<String>) -> Result<i32, String> {
     let mut result_frequency:i32 = starting;
 
     let mut result_freqs:Vec<i32> = vec![starting];
@@ -20,16 +23,14 @@ fn apply_frequency_changes_and_find_repeat(starting:i32, elements:Vec<String>) -
 
     loop {
         for el in &elements {
-            result_frequency = apply_frequency_change(result_frequency, el.parse::<i32>().unwrap());
+            result_frequency = apply_frequency_change(result_frequency, parse_change(el)?);
 
-            if (&result_freqs).into_iter().any(|&f| f == result_frequency) {
+            if result_freqs.contains(&result_frequency) {
                 found_repeat = true;
                 break;
             }
 
             result_freqs.push(result_frequency);
-
-            println!("current: {}", result_frequency);
         }
 
         if found_repeat {
@@ -37,40 +38,28 @@ fn apply_frequency_changes_and_find_repeat(starting:i32, elements:Vec<String>) -
         }
     }
 
-    result_frequency
+    Ok(result_frequency)
 }
 
 fn apply_frequency_change(current:i32, change:i32) -> i32 {
     return current + change;
 }
 
-fn main() -> io::Result<()> {
-    let mut elements = vec![];
-
-    let s = io::stdin();
-
-    for line in s.lock().lines() {
-        let l = line.unwrap().clone();
-
-        if l == "" {
-            break;
-        }
+pub struct Day;
 
-        elements.push(l);
+impl Solution for Day {
+    fn part1(&self, input:&str) -> Answer {
+        Ok(apply_frequency_changes(0, get_lines(input))?.to_string())
     }
 
-    // let result = apply_frequency_changes(0, elements);
-
-    let result = apply_frequency_changes_and_find_repeat(0, elements);
-
-    println!("result: {}", result);
-
-    Ok(())
+    fn part2(&self, input:&str) -> Answer {
+        Ok(apply_frequency_changes_and_find_repeat(0, get_lines(input))?.to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use *;
+    use super::*;
 
     #[test]
     fn test_apply_frequency_change() {
@@ -82,15 +71,17 @@ mod tests {
 
     #[test]
     fn test_apply_frequency_changes() {
-        assert_eq!(apply_frequency_changes(0, vec![String::from("+1"), String::from("+1"), String::from("+1")]), 3);
-        assert_eq!(apply_frequency_changes(0, vec![String::from("+1"), String::from("+1"), String::from("-2")]), 0);
-        assert_eq!(apply_frequency_changes(0, vec![String::from("-1"), String::from("-2"), String::from("-3")]), -6);
+        assert_eq!(apply_frequency_changes(0, vec![String::from("+1"), String::from("+1"), String::from("+1")]), Ok(3));
+        assert_eq!(apply_frequency_changes(0, vec![String::from("+1"), String::from("+1"), String::from("-2")]), Ok(0));
+        assert_eq!(apply_frequency_changes(0, vec![String::from("-1"), String::from("-2"), String::from("-3")]), Ok(-6));
+        assert!(apply_frequency_changes(0, vec![String::from("+1"), String::from("x")]).is_err());
     }
 
     #[test]
     fn test_apply_frequency_changes_and_find_repeat() {
-        assert_eq!(apply_frequency_changes_and_find_repeat(0, vec![String::from("+1"), String::from("-1")]), 0);
-        assert_eq!(apply_frequency_changes_and_find_repeat(0, vec![String::from("+3"), String::from("+3"), String::from("+4"), String::from("-2"), String::from("-4")]), 10);
-        assert_eq!(apply_frequency_changes_and_find_repeat(0, vec![String::from("+7"), String::from("+7"), String::from("-2"), String::from("-7"), String::from("-4")]), 14);
+        assert_eq!(apply_frequency_changes_and_find_repeat(0, vec![String::from("+1"), String::from("-1")]), Ok(0));
+        assert_eq!(apply_frequency_changes_and_find_repeat(0, vec![String::from("+3"), String::from("+3"), String::from("+4"), String::from("-2"), String::from("-4")]), Ok(10));
+        assert_eq!(apply_frequency_changes_and_find_repeat(0, vec![String::from("+7"), String::from("+7"), String::from("-2"), String::from("-7"), String::from("-4")]), Ok(14));
+        assert!(apply_frequency_changes_and_find_repeat(0, vec![String::from("+1"), String::from("")]).is_err());
     }
 }
@@ -0,0 +1,4 @@
+extern crate aoc;
+
+#[path = "1.rs"]
+pub mod day01;
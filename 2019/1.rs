@@ -1,15 +1,15 @@
-use std::env;
-use std::io;
-use std::io::prelude::*;
+use aoc::{get_lines, Answer, Solution};
 
-fn calculate_fuel_total(elements:Vec<String>, include_fuel_mass:bool) -> i32 {
+fn calculate_fuel_total(elements:Vec<String>, include_fuel_mass:bool) -> Result<i32, String> {
     let mut sum : i32 = 0;
 
     for el in elements {
-        sum = sum + calculate_fuel_for_element(el.parse::<i32>().unwrap(), include_fuel_mass);
+        let mass = el.parse::<i32>().map_err(|_| format!("invalid mass {:?}", el))?;
+
+        sum = sum + calculate_fuel_for_element(mass, include_fuel_mass);
     }
 
-    return sum;
+    return Ok(sum);
 }
 
 fn calculate_fuel_for_element(mass:i32, include_fuel_mass:bool) -> i32 {
@@ -26,45 +26,21 @@ fn calculate_fuel_for_element(mass:i32, include_fuel_mass:bool) -> i32 {
     return fuel;
 }
 
-fn main() -> io::Result<()> {
-    let args: Vec<String> = env::args().collect();
-    let mut part: String = "".to_string();
-
-    if args.len() > 1 {
-        part = args[1].clone();
-    }
-
-    let mut elements = vec![];
-
-    let s = io::stdin();
-
-    for line in s.lock().lines() {
-        let l = line.unwrap().clone();
-
-        if l == "" {
-            break;
-        }
+pub struct Day;
 
-        elements.push(l);
+impl Solution for Day {
+    fn part1(&self, input:&str) -> Answer {
+        Ok(calculate_fuel_total(get_lines(input), false)?.to_string())
     }
 
-    let mut include_fuel_mass: bool = false;
-
-    if part == "2" {
-        include_fuel_mass = true;
+    fn part2(&self, input:&str) -> Answer {
+        Ok(calculate_fuel_total(get_lines(input), true)?.to_string())
     }
-
-    let sum = calculate_fuel_total(elements, include_fuel_mass);
-
-    println!("part: {}", part);
-    println!("sum: {}", sum);
-
-    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use calculate_fuel_for_element;
+    use super::{calculate_fuel_for_element, calculate_fuel_total};
 
     #[test]
     fn test_against_given_values_part_1() {
@@ -80,4 +56,10 @@ mod tests {
         assert_eq!(calculate_fuel_for_element(1969, true), 966);
         assert_eq!(calculate_fuel_for_element(100756, true), 50346);
     }
+
+    #[test]
+    fn test_calculate_fuel_total() {
+        assert_eq!(calculate_fuel_total(vec![String::from("12"), String::from("14")], false), Ok(4));
+        assert!(calculate_fuel_total(vec![String::from("12"), String::from("1 4")], false).is_err());
+    }
 }
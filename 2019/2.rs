@@ -1,188 +1,103 @@
-use std::io;
+use aoc::{Answer, Solution};
+use intcode::{default_threads, execute_program, execute_symbolic, get_program_from_string, seek_goal, IntcodeError, Patch, PathEnd, SymbolicPath, DEFAULT_MAX_INSTRUCTIONS};
 
-struct Instruction {
-    code: i32,
-    input1_idx: usize,
-    input2_idx: usize,
-    output_idx: usize,
-}
-
-fn get_program_from_string(string:String) -> Vec<i32> {
-    let mut program:Vec<i32> = vec![];
+// Looks for the noun (address 1) and verb (address 2) that leave target at
+// address 0.
+fn find_noun_and_verb(program:&Vec<i64>, target:i64) -> Result<Option<(i64, i64)>, IntcodeError> {
+    let patches = [Patch::new(1, 0..100), Patch::new(2, 0..100)];
 
-    for token in string.split(","){
-        program.push(token.parse::<i32>().unwrap())
-     }
+    let solution = seek_goal(program, &patches, 0, target, default_threads(), DEFAULT_MAX_INSTRUCTIONS)?;
 
-    return program;
+    return Ok(solution.map(|solution| (solution.values[0], solution.values[1])));
 }
 
-fn execute_program(program:Vec<i32>) -> Vec<i32> {
-    let mut new_program = program.clone();
-
-    let mut pc: usize = 0;
-    let mut current_instruction: i32 = 0;
-
-    while current_instruction != 99 {
-        let instruction = get_instruction_at_index(pc, &new_program);
-
-        current_instruction = instruction.code;
-
-        new_program = execute_instruction(instruction, &new_program);
+// The value left at address 0 as a formula in the noun and verb, if the
+// program gets there without branching on them.
+fn get_formula(program:&Vec<i64>) -> Option<String> {
+    let path = SymbolicPath::new(program, &[(1, "noun"), (2, "verb")], &[]);
 
-        pc = pc + 4;
+    match execute_symbolic(path, 100000).as_slice() {
+        &[(ref path, PathEnd::Halted)] => Some(path.read(0).to_string()),
+        _ => None,
     }
-
-    return new_program;
 }
 
-fn execute_instruction(instruction:Instruction, program:&Vec<i32>) -> Vec<i32> {
-    let mut new_program = program.clone();
-
-    match instruction.code {
-        1 => {
-            let op1 = program[instruction.input1_idx];
-            let op2 = program[instruction.input2_idx];
-            let val = op1 + op2;
-
-            new_program[instruction.output_idx] = val;
-        },
-        2 => {
-            let op1 = program[instruction.input1_idx];
-            let op2 = program[instruction.input2_idx];
-            let val = op1 * op2;
+pub struct Day;
 
-            new_program[instruction.output_idx] = val;
-        },
-        _ => (),
-    };
+impl Solution for Day {
+    // Restores the "1202 program alarm" state before running
+    fn part1(&self, input:&str) -> Answer {
+        let mut program = get_program_from_string(input.trim_end().to_string())?;
 
-    return new_program;
-}
-
-fn get_instruction_at_index(idx:usize, program:&Vec<i32>) -> Instruction {
-    let mut instruction: Instruction = Instruction{
-        code: program[idx],
-        input1_idx: 0,
-        input2_idx: 0,
-        output_idx: 0,
-    };
-
-    if [1,2].contains(&instruction.code) {
-        instruction.input1_idx = program[idx+1] as usize;
-        instruction.input2_idx = program[idx+2] as usize;
-        instruction.output_idx = program[idx+3] as usize;
-    }
-
-    return instruction;
-}
-
-fn modify_program_value_at_index(idx:usize, val: i32, program:Vec<i32>) -> Vec<i32> {
-    let mut new_program = program.clone();
-
-    new_program[idx] = val;
-
-    return new_program;
-}
+        if program.len() < 3 {
+            return Err(format!("expected a noun and verb after the first opcode, got {} words", program.len()).into());
+        }
 
-fn main() -> io::Result<()> {
-    let mut input = String::new();
+        program[1] = 12;
+        program[2] = 2;
 
-    match io::stdin().read_line(&mut input) {
-        Ok(_n) => (),
-        Err(error) => println!("error: {}", error),
+        Ok(execute_program(program, &mut vec![], &mut vec![])?[0].to_string())
     }
 
-    let program = get_program_from_string(input.trim_end().to_string());
-
-    let (mut noun, mut verb, mut output) = (0, 0, 0);
-
-    while noun < 100 {
-        verb = 0;
-        while verb < 100 {
-            let new_program = program.clone();
-
-            let new_program = modify_program_value_at_index(1, noun, new_program);
-            let new_program = modify_program_value_at_index(2, verb, new_program);
-            let new_program = execute_program(new_program);
-
-            output = new_program[0];
+    fn part2(&self, input:&str) -> Answer {
+        let program = get_program_from_string(input.trim_end().to_string())?;
 
-            if output == 19690720 {
-                break;
-            }
-
-            verb = verb + 1;
-        }
-
-        if output == 19690720 {
-            break;
+        match find_noun_and_verb(&program, 19690720)? {
+            Some((noun, verb)) => Ok((100 * noun + verb).to_string()),
+            None => Err("no noun and verb produce 19690720".into()),
         }
-
-        noun = noun + 1;
     }
 
-    // let program = execute_program(program);
-
-    println!("noun: {}", noun);
-    println!("verb: {}", verb);
-
-    println!("100 * noun + verb = {}", 100 * noun + verb);
+    fn notes(&self, input:&str) -> Vec<String> {
+        let program = match get_program_from_string(input.trim_end().to_string()) {
+            Ok(program) => program,
+            Err(_) => return vec![],
+        };
 
-    Ok(())
+        get_formula(&program).map(|formula| format!("[0] = {}", formula)).into_iter().collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use *;
-
-    #[test]
-    fn test_get_instruction_at_index() {
-        let instruction = get_instruction_at_index(4, &vec![0,2,4,5,1,2,3,4]);
+    use super::*;
 
-        assert_eq!(instruction.code, 1);
-        assert_eq!(instruction.input1_idx, 2);
-        assert_eq!(instruction.input2_idx, 3);
-        assert_eq!(instruction.output_idx, 4);
+    // Day 2 programs neither read input nor produce output
+    fn no_input() -> Vec<i64> {
+        vec![]
     }
 
     #[test]
-    fn test_execute_instruction() {
-        let program = vec![1,0,0,0,99];
-        let instruction = Instruction{code: 1, input1_idx: 0, input2_idx: 0, output_idx: 0};
-
-        let new_program = execute_instruction(instruction, &program);
-
-        assert_eq!(new_program, vec![2,0,0,0,99]);
-
-        let program = vec![2,3,0,3,99];
-        let instruction = Instruction{code: 2, input1_idx: 3, input2_idx: 0, output_idx: 3};
-
-        let new_program = execute_instruction(instruction, &program);
-
-        assert_eq!(new_program, vec![2,3,0,6,99]);
-
-        let program = vec![2,4,4,5,99,0];
-        let instruction = Instruction{code: 2, input1_idx: 4, input2_idx: 4, output_idx: 5};
-
-        let new_program = execute_instruction(instruction, &program);
+    fn test_execute_program() {
+        let program = execute_program(vec![1,1,1,4,99,5,6,0,99], &mut no_input(), &mut vec![]).unwrap();
 
-        assert_eq!(new_program, vec![2,4,4,5,99,9801]);
+        assert_eq!(program, vec![30,1,1,4,2,5,6,0,99]);
     }
 
     #[test]
-    fn test_execute_program() {
-        let program = execute_program(vec![1,1,1,4,99,5,6,0,99]);
+    fn test_find_noun_and_verb() {
+        // Leaves [noun] * [verb] at address 0, like the puzzle's programs
+        // combining values picked out by the noun and verb
+        let program = vec![2,0,0,0,99,7,11];
 
-        assert_eq!(program, vec![30,1,1,4,2,5,6,0,99]);
+        assert_eq!(find_noun_and_verb(&program, 77), Ok(Some((5,6))));
+        assert_eq!(find_noun_and_verb(&program, 78), Ok(None));
     }
 
     #[test]
-    fn test_modify_program() {
-        let program = vec![2,4,4,5,99,0];
-
-        let new_program = modify_program_value_at_index(5, 5, program);
+    fn test_get_formula() {
+        // [3] = [noun] + [verb], then [0] = [3] * [13] after [3] is
+        // overwritten with noun + verb
+        let program = vec![1,0,0,3,1,1,2,3,2,3,13,0,99,5];
+
+        assert_eq!(get_formula(&program), Some("noun * 5 + verb * 5".to_string()));
+        assert_eq!(get_formula(&vec![1105,1,0]), None);
+        assert_eq!(Day.notes("1,0,0,3,1,1,2,3,2,3,13,0,99,5\n"), vec!["[0] = noun * 5 + verb * 5".to_string()]);
+    }
 
-        assert_eq!(new_program, vec![2,4,4,5,99,5]);
+    #[test]
+    fn test_short_program() {
+        assert!(Day.part1("99").is_err());
+        assert!(Day.part2("99").is_err());
     }
 }
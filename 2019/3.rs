@@ -1,5 +1,4 @@
-use std::io;
-use std::io::prelude::*;
+use aoc::{get_lines, Answer, Solution};
 
 #[derive(Clone,Copy,PartialEq,Debug)]
 struct Segment {
@@ -55,29 +54,37 @@ struct Vector {
     length: i32,
 }
 
-fn get_path_from_string(string:&str) -> Vec<Vector> {
+fn get_path_from_string(string:&str) -> Result<Vec<Vector>, String> {
     let mut path:Vec<Vector> = vec![];
 
     for token in string.split(","){
-        let mut token = token.to_string();
+        let mut chars = token.chars();
 
-        let dir = match token.remove(0) {
-            'U' => Direction::Up,
-            'D' => Direction::Down,
-            'L' => Direction::Left,
-            'R' => Direction::Right,
+        let dir = match chars.next() {
+            Some('U') => Direction::Up,
+            Some('D') => Direction::Down,
+            Some('L') => Direction::Left,
+            Some('R') => Direction::Right,
             _ => Direction::None,
         };
 
         let v = Vector{
             direction: dir,
-            length: token.parse::<i32>().unwrap(),
+            length: chars.as_str().parse::<i32>().map_err(|_| format!("invalid move {:?}", token))?,
         };
 
         path.push(v);
      }
 
-    return path;
+    return Ok(path);
+}
+
+// The puzzle input is one path per line, for exactly two wires
+fn get_wire_paths(lines:&[String]) -> Result<(Vec<Vector>, Vec<Vector>), String> {
+    match lines {
+        &[ref wire_1, ref wire_2] => Ok((get_path_from_string(wire_1)?, get_path_from_string(wire_2)?)),
+        _ => Err(format!("expected 2 wires, got {}", lines.len())),
+    }
 }
 
 fn get_segments_from_path(start:Point, path:Vec<Vector>) -> Vec<Segment> {
@@ -100,7 +107,7 @@ fn get_segments_from_path(start:Point, path:Vec<Vector>) -> Vec<Segment> {
 
         segments.push(segment);
 
-        current = end.clone();
+        current = end;
     }
 
     return segments;
@@ -122,12 +129,6 @@ fn find_intersections_of_lines(path1:Vec<Segment>, path2:Vec<Segment>) -> Vec<Po
                        ((segment2.end.y < segment1.start.y && segment1.start.y < segment2.start.y) ||
                        (segment2.start.y < segment1.start.y && segment1.start.y < segment2.end.y) ) {
                         intersections.push(Point{ x: segment2.start.x, y: segment1.start.y });
-                        // println!("added intersection");
-                        // println!("point: {:?}", Point{ x: segment2.start.x, y: segment1.start.y });
-                        // println!("segment 1: {:?}", segment1);
-                        // println!("segment 2: {:?}", segment2);
-                        // println!("segment 1 orientation: {:?}", segment1.get_orientation());
-                        // println!("segment 2 orientation: {:?}", segment2.get_orientation());
                     }
                 },
                 Orientation::Vertical => {
@@ -136,12 +137,6 @@ fn find_intersections_of_lines(path1:Vec<Segment>, path2:Vec<Segment>) -> Vec<Po
                        ((segment2.start.x < segment1.start.x && segment1.start.x < segment2.end.x) ||
                        (segment2.end.x < segment1.start.x && segment1.start.x < segment2.start.x)) {
                         intersections.push(Point{ x: segment1.start.x, y: segment2.start.y });
-                        // println!("added intersection");
-                        // println!("point: {:?}", Point{ x: segment1.start.x, y: segment2.start.y });
-                        // println!("segment 1: {:?}", segment1);
-                        // println!("segment 2: {:?}", segment2);
-                        // println!("segment 1 orientation: {:?}", segment1.get_orientation());
-                        // println!("segment 2 orientation: {:?}", segment2.get_orientation());
                     }
                 },
             }
@@ -155,9 +150,8 @@ fn calculate_manhattan_distance(point1:Point, point2:Point) -> i32 {
     return (point1.x - point2.x).abs() + (point1.y - point2.y).abs();
 }
 
-fn find_closest_intersection(lines:Vec<String>) -> (i32, Point) {
-    let wire_1_path = get_path_from_string(&lines[0]);
-    let wire_2_path = get_path_from_string(&lines[1]);
+fn find_closest_intersection(lines:Vec<String>) -> Result<(i32, Point), String> {
+    let (wire_1_path, wire_2_path) = get_wire_paths(&lines)?;
 
     let wire_1_segments = get_segments_from_path(Point{x:1, y:1}, wire_1_path);
     let wire_2_segments = get_segments_from_path(Point{x:1, y:1}, wire_2_path);
@@ -176,12 +170,11 @@ fn find_closest_intersection(lines:Vec<String>) -> (i32, Point) {
         }
     }
 
-    return (shortest_distance, closest_intersection);
+    return Ok((shortest_distance, closest_intersection));
 }
 
-fn find_shortest_path_to_intersection(lines:Vec<String>) -> (i32, Point) {
-    let wire_1_path = get_path_from_string(&lines[0]);
-    let wire_2_path = get_path_from_string(&lines[1]);
+fn find_shortest_path_to_intersection(lines:Vec<String>) -> Result<(i32, Point), String> {
+    let (wire_1_path, wire_2_path) = get_wire_paths(&lines)?;
 
     let wire_1_segments = get_segments_from_path(Point{x:1, y:1}, wire_1_path);
     let wire_2_segments = get_segments_from_path(Point{x:1, y:1}, wire_2_path);
@@ -206,8 +199,6 @@ fn find_shortest_path_to_intersection(lines:Vec<String>) -> (i32, Point) {
                         },
                     }
 
-                    println!("path_length: {}", path_length);
-
                     break;
                 } else {
                     match segment.get_orientation() {
@@ -218,8 +209,6 @@ fn find_shortest_path_to_intersection(lines:Vec<String>) -> (i32, Point) {
                             path_length += (segment.end.y - segment.start.y).abs();
                         },
                     }
-
-                    println!("path_length: {}", path_length);
                 }
             }
 
@@ -236,49 +225,42 @@ fn find_shortest_path_to_intersection(lines:Vec<String>) -> (i32, Point) {
         }
     }
 
-    return (shortest_path, shortest_intersection);
+    return Ok((shortest_path, shortest_intersection));
 }
 
-fn main() -> io::Result<()> {
-    let mut lines = vec![];
-
-    let s = io::stdin();
+pub struct Day;
 
-    for line in s.lock().lines() {
-        let l = line.unwrap().clone();
+impl Solution for Day {
+    fn part1(&self, input:&str) -> Answer {
+        let (shortest_distance, _) = find_closest_intersection(get_lines(input))?;
 
-        if l == "" {
-            break;
-        }
-
-        lines.push(l);
+        Ok(shortest_distance.to_string())
     }
 
-    let (shortest_distance, closest_intersection) = find_closest_intersection(lines.clone());
-    let (shortest_path, intersection) = find_shortest_path_to_intersection(lines.clone());
-
-    println!("shortest_distance: {}", shortest_distance);
-    println!("closest_intersection: {:?}", closest_intersection);
+    fn part2(&self, input:&str) -> Answer {
+        let (shortest_path, _) = find_shortest_path_to_intersection(get_lines(input))?;
 
-    println!("shortest_path: {}", shortest_path);
-    println!("intersection: {:?}", intersection);
-
-    Ok(())
+        Ok(shortest_path.to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use *;
+    use super::*;
 
     #[test]
     fn test_get_path_from_string() {
-        let path = get_path_from_string("U13,L4,R22,D1");
+        let path = get_path_from_string("U13,L4,R22,D1").unwrap();
 
         assert_eq!(path.len(), 4);
         assert_eq!(path[0], Vector{direction:Direction::Up, length:13});
         assert_eq!(path[1], Vector{direction:Direction::Left, length:4});
         assert_eq!(path[2], Vector{direction:Direction::Right, length:22});
         assert_eq!(path[3], Vector{direction:Direction::Down, length:1});
+
+        assert!(get_path_from_string("U13,L").is_err());
+        assert!(get_path_from_string("U13,,R2").is_err());
+        assert!(find_closest_intersection(vec![String::from("U1")]).is_err());
     }
 
     #[test]
@@ -333,20 +315,20 @@ mod tests {
     fn test_find_closest_intersection() {
         let lines = vec![String::from("R8,U5,L5,D3"), String::from("U7,R6,D4,L4")];
 
-        let (shortest_distance, closest_intersection) = find_closest_intersection(lines);
+        let (shortest_distance, closest_intersection) = find_closest_intersection(lines).unwrap();
 
         assert_eq!(shortest_distance, 6);
         assert_eq!(closest_intersection, Point{x:4, y:4});
 
         let lines = vec![String::from("R75,D30,R83,U83,L12,D49,R71,U7,L72"), String::from("U62,R66,U55,R34,D71,R55,D58,R83")];
 
-        let (shortest_distance, _) = find_closest_intersection(lines);
+        let (shortest_distance, _) = find_closest_intersection(lines).unwrap();
 
         assert_eq!(shortest_distance, 159);
 
         let lines = vec![String::from("R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51"), String::from("U98,R91,D20,R16,D67,R40,U7,R15,U6,R7")];
 
-        let (shortest_distance, _) = find_closest_intersection(lines);
+        let (shortest_distance, _) = find_closest_intersection(lines).unwrap();
 
         assert_eq!(shortest_distance, 135);
     }
@@ -355,20 +337,20 @@ mod tests {
     fn test_find_shortest_path_to_intersection() {
         let lines = vec![String::from("R8,U5,L5,D3"), String::from("U7,R6,D4,L4")];
 
-        let (shortest_path, intersection) = find_shortest_path_to_intersection(lines);
+        let (shortest_path, intersection) = find_shortest_path_to_intersection(lines).unwrap();
 
         assert_eq!(shortest_path, 30);
         assert_eq!(intersection, Point{x:7, y:6});
 
         let lines = vec![String::from("R75,D30,R83,U83,L12,D49,R71,U7,L72"), String::from("U62,R66,U55,R34,D71,R55,D58,R83")];
 
-        let (shortest_path, _) = find_shortest_path_to_intersection(lines);
+        let (shortest_path, _) = find_shortest_path_to_intersection(lines).unwrap();
 
         assert_eq!(shortest_path, 610);
 
         let lines = vec![String::from("R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51"), String::from("U98,R91,D20,R16,D67,R40,U7,R15,U6,R7")];
 
-        let (shortest_path, _) = find_shortest_path_to_intersection(lines);
+        let (shortest_path, _) = find_shortest_path_to_intersection(lines).unwrap();
 
         assert_eq!(shortest_path, 410);
     }
@@ -1,85 +1,90 @@
-use std::io;
-use std::io::Write;
+use aoc::{Answer, Solution};
 
-fn main() -> io::Result<()> {
-    let (mut input1, mut input2) = (String::new(), String::new());
+// Six digits that never decrease, with at least one run of two matching
+// digits; with exact_pair that run can't be part of a longer one.
+fn is_password(n:i32, exact_pair:bool) -> bool {
+    let digits: Vec<u32> = n.to_string().chars().map(|c| c.to_digit(10).unwrap()).collect();
 
-    io::stdout().write_all(b"Enter lower bound: ")?;
-    io::stdout().flush()?;
-
-    match io::stdin().read_line(&mut input1) {
-        Ok(_n) => (),
-        Err(error) => println!("error: {}", error),
+    if digits.len() != 6 {
+        return false;
     }
 
-    io::stdout().write_all(b"Enter upper bound: ")?;
-    io::stdout().flush()?;
-
-    match io::stdin().read_line(&mut input2) {
-        Ok(_n) => (),
-        Err(error) => println!("error: {}", error),
+    if digits.windows(2).any(|pair| pair[1] < pair[0]) {
+        return false;
     }
 
-    input1 = input1.trim_end().to_string();
-    input2 = input2.trim_end().to_string();
-
-    println!("lower bound: {}", input1);
-    println!("upper bound: {}", input2);
+    let mut run_length = 1;
 
-    let low = input1.parse::<i32>().unwrap();
-    let high = input2.parse::<i32>().unwrap();
-
-    let mut keepers = vec![];
+    for i in 1..=digits.len() {
+        if i < digits.len() && digits[i] == digits[i - 1] {
+            run_length += 1;
+            continue;
+        }
 
-    for n in low..=high {
-        let n_string = n.to_string();
+        if run_length == 2 || (run_length > 2 && !exact_pair) {
+            return true;
+        }
 
-        let (mut prev_int, mut num_digits) = (0, 0);
+        run_length = 1;
+    }
 
-        for c in n_string.chars() {
-            let c_int = c.to_digit(10).unwrap();
+    return false;
+}
 
-            if c_int < prev_int {
-                break;
-            }
+// The puzzle input is the range as "low-high"
+fn get_bounds_from_string(input:&str) -> Result<(i32, i32), String> {
+    let bounds: Vec<&str> = input.trim().split('-').collect();
 
-            prev_int = c_int;
-            num_digits += 1;
-        }
+    match bounds.as_slice() {
+        &[low, high] => match (low.parse::<i32>(), high.parse::<i32>()) {
+            (Ok(low), Ok(high)) => Ok((low, high)),
+            _ => Err(format!("invalid range {:?}", input.trim())),
+        },
+        _ => Err(format!("expected low-high, got {:?}", input.trim())),
+    }
+}
 
-        let mut has_dupes = false;
-        prev_int = 0;
-        let mut num_dupe_chars = 0;
+fn count_passwords(input:&str, exact_pair:bool) -> Answer {
+    let (low, high) = get_bounds_from_string(input)?;
 
-        for c in n_string.chars() {
-            let c_int = c.to_digit(10).unwrap();
+    Ok((low..=high).filter(|&n| is_password(n, exact_pair)).count().to_string())
+}
 
-            if c_int == prev_int {
-                num_dupe_chars += 1;
+pub struct Day;
 
-                if num_dupe_chars == 1 {
-                    has_dupes = true;
-                } else {
-                    has_dupes = false;
-                }
-            } else {
-                if has_dupes {
-                    break;
-                }
+impl Solution for Day {
+    fn part1(&self, input:&str) -> Answer {
+        count_passwords(input, false)
+    }
 
-                num_dupe_chars = 0;
-            }
+    fn part2(&self, input:&str) -> Answer {
+        count_passwords(input, true)
+    }
+}
 
-            prev_int = c_int;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if num_digits == 6 && has_dupes {
-            keepers.push(n);
-        }
+    #[test]
+    fn test_is_password_part_1() {
+        assert!(is_password(111111, false));
+        assert!(!is_password(223450, false));
+        assert!(!is_password(123789, false));
+        assert!(!is_password(11111, false));
     }
 
-    println!("keepers: {:?}", keepers);
-    println!("number of keepers: {}", keepers.len());
+    #[test]
+    fn test_is_password_part_2() {
+        assert!(is_password(112233, true));
+        assert!(!is_password(123444, true));
+        assert!(is_password(111122, true));
+    }
 
-    Ok(())
+    #[test]
+    fn test_get_bounds_from_string() {
+        assert_eq!(get_bounds_from_string("123-456\n"), Ok((123, 456)));
+        assert!(get_bounds_from_string("123").is_err());
+        assert!(get_bounds_from_string("a-456").is_err());
+    }
 }
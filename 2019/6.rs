@@ -1,81 +1,114 @@
-
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::io::{self, BufRead};
 
-fn main() {
-    let mut count = 0;
-    let orbits = io::stdin()
-        .lock()
-        .lines()
-        .map(|l| l.unwrap())
-        .map(|l| (l.split(")").nth(1).unwrap().to_string(), l.split(")").nth(0).unwrap().to_string()))
-        .collect::<HashMap<_,_>>();
-
-    println!("orbits: {:?}", orbits);
-
-    // part 1
-    for mut body in orbits.keys() {
-        while let Some(parent) = orbits.get(body) {
-            count += 1;
-            body = parent;
-        }
+use aoc::{Answer, Solution};
+
+// Maps every body to the one it directly orbits, from lines like "COM)B"
+fn parse_orbits(input:&str) -> Result<HashMap<String, String>, String> {
+    let mut orbits = HashMap::new();
+
+    for line in input.lines().filter(|l| !l.is_empty()) {
+        match line.find(")") {
+            Some(i) => orbits.insert(line[i + 1..].to_string(), line[..i].to_string()),
+            None => return Err(format!("expected parent)body, got {:?}", line)),
+        };
     }
 
-    println!("{}", count);
+    return Ok(orbits);
+}
 
-    // part 2
-    let mut you_parents = HashSet::new();
-    let mut san_parents = HashSet::new();
+// Every body the given one orbits, closest first. Fails if the orbits loop
+// back on themselves.
+fn get_parents<'a>(orbits:&'a HashMap<String, String>, mut body:&'a str) -> Result<Vec<&'a str>, String> {
+    let mut parents = vec![];
+    let mut visited = HashSet::new();
 
-    let mut body = "YOU";
+    visited.insert(body);
 
     while let Some(parent) = orbits.get(body) {
-        you_parents.insert(parent);
+        if !visited.insert(parent.as_str()) {
+            return Err(format!("{} orbits itself", parent));
+        }
+
+        parents.push(parent.as_str());
         body = parent;
     }
 
-    body = "SAN";
+    return Ok(parents);
+}
 
-    while let Some(parent) = orbits.get(body) {
-        san_parents.insert(parent);
-        body = parent;
-    }
+// Direct and indirect orbits of every body
+fn count_orbits(orbits:&HashMap<String, String>) -> Result<usize, String> {
+    let mut count = 0;
 
-    println!("you_parents: {:?}", you_parents);
-    println!("san_parents: {:?}", san_parents);
+    for body in orbits.keys() {
+        count = count + get_parents(orbits, body)?.len();
+    }
 
-    let common_parents = you_parents.intersection(&san_parents);
+    return Ok(count);
+}
 
-    println!("common_parents: {:?}", common_parents);
+// Orbital transfers needed to move from the body YOU orbits to the one SAN
+// orbits, through their closest common parent.
+fn count_transfers(orbits:&HashMap<String, String>) -> Result<Option<usize>, String> {
+    let you_parents = get_parents(orbits, "YOU")?;
+    let san_parents = get_parents(orbits, "SAN")?;
 
-    let mut lowest_count = 100000;
+    let san_set: HashSet<&str> = san_parents.iter().cloned().collect();
 
-    for com in common_parents {
-        let mut count = 0;
+    let (you_count, common) = match you_parents.iter().enumerate().find(|&(_, parent)| san_set.contains(parent)) {
+        Some(found) => found,
+        None => return Ok(None),
+    };
 
-        body = "YOU";
+    return Ok(san_parents.iter().position(|parent| parent == common).map(|san_count| you_count + san_count));
+}
 
-        while let Some(parent) = orbits.get(body) {
-            body = parent;
+pub struct Day;
 
-            if &parent == com { break; }
+impl Solution for Day {
+    fn part1(&self, input:&str) -> Answer {
+        Ok(count_orbits(&parse_orbits(input)?)?.to_string())
+    }
 
-            count += 1;
+    fn part2(&self, input:&str) -> Answer {
+        match count_transfers(&parse_orbits(input)?)? {
+            Some(count) => Ok(count.to_string()),
+            None => Err("YOU and SAN don't orbit a common body".into()),
         }
+    }
+}
 
-        body = "SAN";
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        while let Some(parent) = orbits.get(body) {
-            body = parent;
+    #[test]
+    fn test_count_orbits() {
+        let orbits = parse_orbits("COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\n").unwrap();
 
-            if &parent == com { break; }
+        assert_eq!(count_orbits(&orbits), Ok(42));
+    }
 
-            count += 1;
-        }
+    #[test]
+    fn test_count_transfers() {
+        let orbits = parse_orbits("COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\nK)YOU\nI)SAN\n").unwrap();
+
+        assert_eq!(count_transfers(&orbits), Ok(Some(4)));
+        assert_eq!(count_transfers(&parse_orbits("COM)YOU\nX)SAN").unwrap()), Ok(None));
+    }
+
+    #[test]
+    fn test_cyclic_orbits() {
+        let orbits = parse_orbits("A)B\nB)A\n").unwrap();
 
-        if count < lowest_count { lowest_count = count; }
+        assert!(count_orbits(&orbits).is_err());
+        assert!(get_parents(&orbits, "A").is_err());
+        assert!(count_transfers(&parse_orbits("A)B\nB)C\nC)A\nC)YOU\nB)SAN").unwrap()).is_err());
     }
 
-    println!("{}", lowest_count);
+    #[test]
+    fn test_parse_orbits() {
+        assert!(parse_orbits("COM-B").is_err());
+    }
 }
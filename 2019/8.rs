@@ -1,80 +1,64 @@
-use std::io;
-use std::io::Write;
+use aoc::{Answer, Solution};
 
 const WIDTH: usize = 25;
 const HEIGHT: usize = 6;
 
-fn main() -> io::Result<()> {
-    let mut input = String::new();
+// The number of 1 digits times the number of 2 digits on the layer with the
+// fewest 0 digits
+fn get_checksum(layers:&Vec<Vec<i32>>) -> usize {
+    let count = |layer:&Vec<i32>, digit:i32| layer.iter().filter(|&&i| i == digit).count();
 
-    match io::stdin().read_line(&mut input) {
-        Ok(_n) => (),
-        Err(error) => println!("error: {}", error),
+    match layers.iter().min_by_key(|layer| count(layer, 0)) {
+        Some(layer) => count(layer, 1) * count(layer, 2),
+        None => 0,
     }
+}
 
-    let raw = get_raw_from_string(input.trim_end().to_string());
-
-    let layers = get_layers_from_raw(raw, WIDTH, HEIGHT);
-
-    let layers_with_num_zeros = layers.iter().zip(layers.iter().map(|l| l.iter().filter(|&&i| i == 0).count()));
+// Each pixel takes the color of the first layer where it isn't transparent (2)
+fn get_image_from_layers(layers:&Vec<Vec<i32>>, width:usize, height:usize) -> Vec<i32> {
+    let mut result_image = vec![];
 
-    let mut least_zeros_layer = &vec![];
-    let mut least_zeros = None;
+    for i in 0..width*height {
+        let pixel = layers.iter().map(|layer| layer[i]).find(|&pixel| pixel != 2).unwrap_or(2);
 
-    for (layer, num) in layers_with_num_zeros {
-        match least_zeros {
-            None => {
-                least_zeros_layer = layer;
-                least_zeros = Some(num);
-            },
-            Some(z) => {
-                least_zeros_layer = if num < z { layer } else { least_zeros_layer };
-                least_zeros = Some(if num < z { num } else { z });
-            },
-        }
+        result_image.push(pixel);
     }
 
-    let num_ones = least_zeros_layer.iter().filter(|&&i| i == 1).count();
-    let num_twos = least_zeros_layer.iter().filter(|&&i| i == 2).count();
-
-    println!("num_ones * num_twos: {}", num_ones * num_twos);
-
-    let mut result_image = vec![];
-
-    for i in 0..WIDTH*HEIGHT {
-        let mut pixel = 2;
-        let mut j = 0;
+    return result_image;
+}
 
-        while pixel == 2 {
-            pixel = layers[j][i];
+fn render_image(image:Vec<i32>, width:usize, height:usize) -> String {
+    let mut rendered = String::new();
 
-            j += 1;
+    for y in 0..height {
+        for x in 0..width {
+            rendered.push(if image[width*y + x] == 1 { '#' } else { ' ' });
         }
 
-        result_image.push(pixel);
+        rendered.push('\n');
     }
 
-    println!("result_image: {:?}", result_image);
+    return rendered;
+}
+
+pub struct Day;
 
-    print_image(result_image);
+impl Solution for Day {
+    fn part1(&self, input:&str) -> Answer {
+        let layers = get_layers_from_raw(get_raw_from_string(input.trim_end().to_string())?, WIDTH, HEIGHT);
 
-    Ok(())
-}
+        Ok(get_checksum(&layers).to_string())
+    }
 
-fn print_image(image:Vec<i32>) {
-    for y in 0..HEIGHT {
-        for x in 0..WIDTH {
-            let s = if image[WIDTH*y + x] == 0 { " " } else { "#"};
+    fn part2(&self, input:&str) -> Answer {
+        let layers = get_layers_from_raw(get_raw_from_string(input.trim_end().to_string())?, WIDTH, HEIGHT);
 
-            io::stdout().write_all(s.as_bytes());
-        }
-        io::stdout().write_all(b"\n");
+        Ok(render_image(get_image_from_layers(&layers, WIDTH, HEIGHT), WIDTH, HEIGHT))
     }
-    io::stdout().flush();
 }
 
-fn get_raw_from_string(input:String) -> Vec<i32> {
-    input.chars().map(|c| c.to_digit(10).unwrap() as i32).collect()
+fn get_raw_from_string(input:String) -> Result<Vec<i32>, String> {
+    input.chars().map(|c| c.to_digit(10).map(|digit| digit as i32).ok_or_else(|| format!("invalid pixel {:?}", c))).collect()
 }
 
 fn get_layers_from_raw(raw: Vec<i32>, width:usize, height:usize) -> Vec<Vec<i32>> {
@@ -83,9 +67,29 @@ fn get_layers_from_raw(raw: Vec<i32>, width:usize, height:usize) -> Vec<Vec<i32>
 
 #[cfg(test)]
 mod tests {
-    use *;
+    use super::*;
 
     #[test]
-    fn test_execute_program() {
+    fn test_get_layers_from_raw() {
+        let raw = get_raw_from_string("123456789012".to_string()).unwrap();
+
+        assert_eq!(raw, vec![1,2,3,4,5,6,7,8,9,0,1,2]);
+        assert!(get_raw_from_string("12a".to_string()).is_err());
+        assert_eq!(get_layers_from_raw(raw, 3, 2), vec![vec![1,2,3,4,5,6], vec![7,8,9,0,1,2]]);
+    }
+
+    #[test]
+    fn test_get_image_from_layers() {
+        let layers = get_layers_from_raw(get_raw_from_string("0222112222120000".to_string()).unwrap(), 2, 2);
+
+        assert_eq!(get_image_from_layers(&layers, 2, 2), vec![0,1,1,0]);
+        assert_eq!(render_image(vec![0,1,1,0], 2, 2), " #\n# \n");
+    }
+
+    #[test]
+    fn test_get_checksum() {
+        let layers = get_layers_from_raw(get_raw_from_string("123456789012".to_string()).unwrap(), 3, 2);
+
+        assert_eq!(get_checksum(&layers), 1);
     }
 }
@@ -0,0 +1,29 @@
+extern crate aoc;
+extern crate intcode;
+
+#[path = "1.rs"]
+pub mod day01;
+
+#[path = "2.rs"]
+pub mod day02;
+
+#[path = "3.rs"]
+pub mod day03;
+
+#[path = "4.rs"]
+pub mod day04;
+
+#[path = "5.rs"]
+pub mod day05;
+
+#[path = "6.rs"]
+pub mod day06;
+
+#[path = "7.rs"]
+pub mod day07;
+
+#[path = "8.rs"]
+pub mod day08;
+
+#[path = "9.rs"]
+pub mod day09;
@@ -0,0 +1,275 @@
+mod store;
+
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+pub use store::{parse_answers, Answers, Store};
+
+pub type Answer = Result<String, Box<dyn Error>>;
+
+// Implemented by every day. Both parts get the whole puzzle input as given,
+// trailing newline included.
+pub trait Solution {
+    fn part1(&self, input:&str) -> Answer;
+    fn part2(&self, input:&str) -> Answer;
+
+    // Anything else worth showing about the input, printed by `aoc run`
+    // after the answers. Never checked against recorded answers.
+    fn notes(&self, _input:&str) -> Vec<String> {
+        vec![]
+    }
+}
+
+// The input's lines up to the first blank one, for puzzles with one entry per line
+pub fn get_lines(input:&str) -> Vec<String> {
+    input.lines().take_while(|l| !l.is_empty()).map(|l| l.to_string()).collect()
+}
+
+#[derive(PartialEq,Debug)]
+pub struct RunOptions {
+    pub year: u32,
+    pub day: u32,
+    // Both parts are run when no part is given
+    pub part: Option<u32>,
+    // Taken from the store, or else stdin, when no path is given
+    pub input: Option<String>,
+}
+
+fn parse_number(flag:&str, value:Option<&String>) -> Result<u32, String> {
+    match value.map(|value| value.parse::<u32>()) {
+        Some(Ok(number)) => Ok(number),
+        Some(Err(_)) => Err(format!("{} needs a number, got {:?}", flag, value.unwrap())),
+        None => Err(format!("{} needs a number", flag)),
+    }
+}
+
+// Parses the arguments following `run`, e.g. --year 2019 --day 7 --part 2 --input path.
+pub fn parse_run_args(args:&[String]) -> Result<RunOptions, String> {
+    let (mut year, mut day, mut part, mut input) = (None, None, None, None);
+    let mut i = 0;
+
+    while i < args.len() {
+        let value = args.get(i + 1);
+
+        match args[i].as_str() {
+            "--year" => year = Some(parse_number("--year", value)?),
+            "--day" => day = Some(parse_number("--day", value)?),
+            "--part" => part = Some(parse_number("--part", value)?),
+            "--input" => match value {
+                Some(path) => input = Some(path.clone()),
+                None => return Err("--input needs a path".to_string()),
+            },
+            arg => return Err(format!("unknown argument {:?}", arg)),
+        }
+
+        i = i + 2;
+    }
+
+    if part.is_some_and(|part| part != 1 && part != 2) {
+        return Err(format!("there is no part {}", part.unwrap()));
+    }
+
+    match (year, day) {
+        (Some(year), Some(day)) => Ok(RunOptions { year: year, day: day, part: part, input: input }),
+        _ => Err("--year and --day are required".to_string()),
+    }
+}
+
+#[derive(PartialEq,Debug)]
+pub struct VerifyOptions {
+    // Every year and day with a solution is verified when none is given
+    pub year: Option<u32>,
+    pub day: Option<u32>,
+}
+
+// Parses the arguments following `verify`, e.g. --year 2019 --day 7.
+pub fn parse_verify_args(args:&[String]) -> Result<VerifyOptions, String> {
+    let mut options = VerifyOptions { year: None, day: None };
+    let mut i = 0;
+
+    while i < args.len() {
+        let value = args.get(i + 1);
+
+        match args[i].as_str() {
+            "--year" => options.year = Some(parse_number("--year", value)?),
+            "--day" => options.day = Some(parse_number("--day", value)?),
+            arg => return Err(format!("unknown argument {:?}", arg)),
+        }
+
+        i = i + 2;
+    }
+
+    return Ok(options);
+}
+
+pub fn run_part(solution:&dyn Solution, part:u32, input:&str) -> (Answer, Duration) {
+    let start = Instant::now();
+
+    let answer = if part == 1 { solution.part1(input) } else { solution.part2(input) };
+
+    return (answer, start.elapsed());
+}
+
+pub fn format_duration(duration:Duration) -> String {
+    let micros = duration.as_micros();
+
+    if micros < 1000 {
+        return format!("{}µs", micros);
+    }
+
+    if micros < 1000000 {
+        return format!("{:.2}ms", micros as f64 / 1000.0);
+    }
+
+    return format!("{:.2}s", micros as f64 / 1000000.0);
+}
+
+// One line per part; answers spanning several lines (like rendered images)
+// start on the line after the label.
+pub fn format_answer(part:u32, answer:&Answer, duration:Duration) -> String {
+    match *answer {
+        Ok(ref text) if text.contains('\n') => format!("part {} ({}):\n{}", part, format_duration(duration), text.trim_end()),
+        Ok(ref text) => format!("part {}: {} ({})", part, text, format_duration(duration)),
+        Err(ref error) => format!("part {}: error: {} ({})", part, error, format_duration(duration)),
+    }
+}
+
+// How an answer compares to the recorded one
+#[derive(PartialEq,Debug)]
+pub enum Verdict {
+    Correct,
+    Wrong { expected: String, actual: String },
+    Failed(String),
+    Unrecorded(String),
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Verdict::Correct => write!(f, "ok"),
+            Verdict::Wrong { ref expected, ref actual } if actual.contains('\n') || expected.contains('\n') => {
+                write!(f, "wrong answer:\n{}\nexpected:\n{}", actual.trim_end(), expected.trim_end())
+            },
+            Verdict::Wrong { ref expected, ref actual } => write!(f, "wrong answer {}, expected {}", actual, expected),
+            Verdict::Failed(ref error) => write!(f, "error: {}", error),
+            Verdict::Unrecorded(ref actual) if actual.contains('\n') => write!(f, "no recorded answer, got:\n{}", actual.trim_end()),
+            Verdict::Unrecorded(ref actual) => write!(f, "no recorded answer, got {}", actual),
+        }
+    }
+}
+
+// Trailing whitespace on each line is ignored, so rendered images still match
+// after an editor strips it from the answer file.
+fn normalize_answer(answer:&str) -> String {
+    answer.lines().map(|l| l.trim_end()).collect::<Vec<&str>>().join("\n").trim_end().to_string()
+}
+
+pub fn check_answer(answer:&Answer, expected:Option<&str>) -> Verdict {
+    match (answer, expected) {
+        (&Err(ref error), _) => Verdict::Failed(error.to_string()),
+        (&Ok(ref actual), None) => Verdict::Unrecorded(actual.clone()),
+        (&Ok(ref actual), Some(expected)) if normalize_answer(actual) == normalize_answer(expected) => Verdict::Correct,
+        (&Ok(ref actual), Some(expected)) => Verdict::Wrong { expected: expected.to_string(), actual: actual.clone() },
+    }
+}
+
+// Like format_answer, with multi-line verdicts starting after the label
+pub fn format_verdict(year:u32, day:u32, part:u32, verdict:&Verdict, duration:Duration) -> String {
+    let text = verdict.to_string();
+
+    if text.contains('\n') {
+        return format!("{} day {} part {} ({}): {}", year, day, part, format_duration(duration), text);
+    }
+
+    return format!("{} day {} part {}: {} ({})", year, day, part, text, format_duration(duration));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Example;
+
+    impl Solution for Example {
+        fn part1(&self, input:&str) -> Answer {
+            Ok(input.trim().len().to_string())
+        }
+
+        fn part2(&self, _input:&str) -> Answer {
+            Err("not solved yet".into())
+        }
+    }
+
+    fn to_args(args:&[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_get_lines() {
+        assert_eq!(get_lines("+1\n-2\n\nignored\n"), vec!["+1", "-2"]);
+        assert_eq!(get_lines(""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_parse_run_args() {
+        let options = parse_run_args(&to_args(&["--year", "2019", "--day", "7", "--part", "2", "--input", "in.txt"])).unwrap();
+
+        assert_eq!(options, RunOptions { year: 2019, day: 7, part: Some(2), input: Some("in.txt".to_string()) });
+
+        let options = parse_run_args(&to_args(&["--day", "1", "--year", "2018"])).unwrap();
+
+        assert_eq!(options, RunOptions { year: 2018, day: 1, part: None, input: None });
+    }
+
+    #[test]
+    fn test_parse_run_args_errors() {
+        assert_eq!(parse_run_args(&to_args(&["--year", "2019"])), Err("--year and --day are required".to_string()));
+        assert_eq!(parse_run_args(&to_args(&["--year", "x", "--day", "1"])), Err("--year needs a number, got \"x\"".to_string()));
+        assert_eq!(parse_run_args(&to_args(&["--year", "2019", "--day", "1", "--part", "3"])), Err("there is no part 3".to_string()));
+        assert_eq!(parse_run_args(&to_args(&["--verbose"])), Err("unknown argument \"--verbose\"".to_string()));
+    }
+
+    #[test]
+    fn test_parse_verify_args() {
+        assert_eq!(parse_verify_args(&[]), Ok(VerifyOptions { year: None, day: None }));
+        assert_eq!(parse_verify_args(&to_args(&["--year", "2019", "--day", "7"])), Ok(VerifyOptions { year: Some(2019), day: Some(7) }));
+        assert_eq!(parse_verify_args(&to_args(&["--part", "1"])), Err("unknown argument \"--part\"".to_string()));
+    }
+
+    #[test]
+    fn test_run_part() {
+        let (answer, _) = run_part(&Example, 1, "abc\n");
+
+        assert_eq!(answer.unwrap(), "3");
+        assert!(run_part(&Example, 2, "").0.is_err());
+    }
+
+    #[test]
+    fn test_format_answer() {
+        assert_eq!(format_answer(1, &Ok("42".to_string()), Duration::from_micros(1500)), "part 1: 42 (1.50ms)");
+        assert_eq!(format_answer(2, &Ok("#.\n.#\n".to_string()), Duration::from_micros(20)), "part 2 (20µs):\n#.\n.#");
+        assert_eq!(format_answer(2, &Err("not solved yet".into()), Duration::from_secs(2)), "part 2: error: not solved yet (2.00s)");
+    }
+
+    #[test]
+    fn test_check_answer() {
+        assert_eq!(check_answer(&Ok("42".to_string()), Some("42")), Verdict::Correct);
+        assert_eq!(check_answer(&Ok("# \n #\n".to_string()), Some("#\n #")), Verdict::Correct);
+        assert_eq!(check_answer(&Ok("41".to_string()), Some("42")), Verdict::Wrong { expected: "42".to_string(), actual: "41".to_string() });
+        assert_eq!(check_answer(&Ok("41".to_string()), None), Verdict::Unrecorded("41".to_string()));
+        assert_eq!(check_answer(&Err("not solved yet".into()), Some("42")), Verdict::Failed("not solved yet".to_string()));
+    }
+
+    #[test]
+    fn test_format_verdict() {
+        let wrong = Verdict::Wrong { expected: "42".to_string(), actual: "41".to_string() };
+
+        assert_eq!(format_verdict(2019, 7, 1, &Verdict::Correct, Duration::from_micros(1500)), "2019 day 7 part 1: ok (1.50ms)");
+        assert_eq!(format_verdict(2019, 7, 2, &wrong, Duration::from_micros(20)), "2019 day 7 part 2: wrong answer 41, expected 42 (20µs)");
+
+        let wrong = Verdict::Wrong { expected: "#.\n".to_string(), actual: ".#\n".to_string() };
+
+        assert_eq!(format_verdict(2019, 8, 2, &wrong, Duration::from_micros(20)), "2019 day 8 part 2 (20µs): wrong answer:\n.#\nexpected:\n#.");
+    }
+}
@@ -0,0 +1,219 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// Answers recorded for a day; a part is None until it's been solved.
+#[derive(PartialEq,Debug,Default)]
+pub struct Answers {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+impl Answers {
+    pub fn get(&self, part:u32) -> Option<&str> {
+        match part {
+            1 => self.part1.as_deref(),
+            2 => self.part2.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+fn parse_string(line:usize, value:&str) -> Result<String, String> {
+    let mut result = String::new();
+    let mut chars = value[1..].chars();
+
+    loop {
+        match chars.next() {
+            Some('"') => break,
+            Some('\\') => match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some('"') => result.push('"'),
+                Some('\\') => result.push('\\'),
+                c => return Err(format!("line {}: invalid escape {:?}", line, c)),
+            },
+            Some(c) => result.push(c),
+            None => return Err(format!("line {}: unterminated string", line)),
+        }
+    }
+
+    let rest = chars.as_str().trim();
+
+    if !rest.is_empty() && !rest.starts_with('#') {
+        return Err(format!("line {}: unexpected {:?} after string", line, rest));
+    }
+
+    return Ok(result);
+}
+
+// Just enough TOML for answer files: `part1` and `part2` set to integers,
+// strings or, for answers spanning several lines, multi-line strings without
+// escapes. Comments and blank lines are skipped.
+pub fn parse_answers(text:&str) -> Result<Answers, String> {
+    let mut answers = Answers::default();
+    let mut lines = text.lines().enumerate().map(|(i, l)| (i + 1, l));
+
+    while let Some((line, l)) = lines.next() {
+        let l = l.trim();
+
+        if l.is_empty() || l.starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = match l.find('=') {
+            Some(i) => (l[..i].trim(), l[i + 1..].trim()),
+            None => return Err(format!("line {}: expected key = value", line)),
+        };
+
+        let answer = if let Some(value) = value.strip_prefix("\"\"\"") {
+            let mut block = vec![];
+            let mut rest = Some(value);
+
+            // A newline right after the opening quotes isn't part of the string
+            if value.is_empty() {
+                rest = lines.next().map(|(_, l)| l);
+            }
+
+            loop {
+                match rest {
+                    Some(l) => match l.find("\"\"\"") {
+                        Some(end) => {
+                            block.push(&l[..end]);
+                            break;
+                        },
+                        None => block.push(l),
+                    },
+                    None => return Err(format!("line {}: unterminated multi-line string", line)),
+                }
+
+                rest = lines.next().map(|(_, l)| l);
+            }
+
+            block.join("\n")
+        } else if value.starts_with('"') {
+            parse_string(line, value)?
+        } else {
+            let number = value.split('#').next().unwrap_or("").trim();
+
+            match number.parse::<i64>() {
+                Ok(number) => number.to_string(),
+                Err(_) => return Err(format!("line {}: invalid value {:?}", line, value)),
+            }
+        };
+
+        match key {
+            "part1" => answers.part1 = Some(answer),
+            "part2" => answers.part2 = Some(answer),
+            _ => return Err(format!("line {}: unknown key {:?}", line, key)),
+        }
+    }
+
+    return Ok(answers);
+}
+
+// Puzzle inputs and recorded answers under one directory, as
+// inputs/2019/07.txt and answers/2019/07.toml.
+pub struct Store {
+    root: PathBuf,
+}
+
+impl Store {
+    pub fn new<P: Into<PathBuf>>(root:P) -> Store {
+        Store { root: root.into() }
+    }
+
+    pub fn input_path(&self, year:u32, day:u32) -> PathBuf {
+        self.root.join("inputs").join(year.to_string()).join(format!("{:02}.txt", day))
+    }
+
+    pub fn answers_path(&self, year:u32, day:u32) -> PathBuf {
+        self.root.join("answers").join(year.to_string()).join(format!("{:02}.toml", day))
+    }
+
+    // None when there's no input stored for the day
+    pub fn load_input(&self, year:u32, day:u32) -> io::Result<Option<String>> {
+        match fs::read_to_string(self.input_path(year, day)) {
+            Ok(input) => Ok(Some(input)),
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    // No answers are recorded when the file doesn't exist
+    pub fn load_answers(&self, year:u32, day:u32) -> io::Result<Answers> {
+        let path = self.answers_path(year, day);
+
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(Answers::default()),
+            Err(error) => return Err(error),
+        };
+
+        match parse_answers(&text) {
+            Ok(answers) => Ok(answers),
+            Err(error) => Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), error))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn test_parse_answers() {
+        let answers = parse_answers("# 2019 day 7\npart1 = 43210 # thrusters\npart2 = \"139629729\"\n").unwrap();
+
+        assert_eq!(answers, Answers { part1: Some("43210".to_string()), part2: Some("139629729".to_string()) });
+        assert_eq!(answers.get(1), Some("43210"));
+        assert_eq!(answers.get(3), None);
+
+        let answers = parse_answers("part2 = \"a \\\"b\\\"\\nc\"").unwrap();
+
+        assert_eq!(answers, Answers { part1: None, part2: Some("a \"b\"\nc".to_string()) });
+    }
+
+    #[test]
+    fn test_parse_multi_line_answer() {
+        let answers = parse_answers("part2 = \"\"\"\n#  #\n ## \n\"\"\"\npart1 = 1").unwrap();
+
+        assert_eq!(answers.part2, Some("#  #\n ## \n".to_string()));
+        assert_eq!(answers.part1, Some("1".to_string()));
+
+        assert_eq!(parse_answers("part1 = \"\"\"one line\"\"\"").unwrap().part1, Some("one line".to_string()));
+    }
+
+    #[test]
+    fn test_parse_answers_errors() {
+        assert_eq!(parse_answers("part1"), Err("line 1: expected key = value".to_string()));
+        assert_eq!(parse_answers("\npart3 = 1"), Err("line 2: unknown key \"part3\"".to_string()));
+        assert_eq!(parse_answers("part1 = abc"), Err("line 1: invalid value \"abc\"".to_string()));
+        assert_eq!(parse_answers("part1 = \"abc"), Err("line 1: unterminated string".to_string()));
+        assert_eq!(parse_answers("part1 = \"\"\"\nabc"), Err("line 1: unterminated multi-line string".to_string()));
+    }
+
+    #[test]
+    fn test_store() {
+        let root = env::temp_dir().join(format!("aoc-store-test-{}", process::id()));
+        let store = Store::new(root.clone());
+
+        assert_eq!(store.input_path(2019, 7), root.join("inputs").join("2019").join("07.txt"));
+        assert_eq!(store.load_input(2019, 7).unwrap(), None);
+        assert_eq!(store.load_answers(2019, 7).unwrap(), Answers::default());
+
+        fs::create_dir_all(root.join("inputs").join("2019")).unwrap();
+        fs::create_dir_all(root.join("answers").join("2019")).unwrap();
+        fs::write(store.input_path(2019, 7), "3,0,4,0,99\n").unwrap();
+        fs::write(store.answers_path(2019, 7), "part1 = 5\n").unwrap();
+        fs::write(store.answers_path(2019, 8), "part1 = five\n").unwrap();
+
+        assert_eq!(store.load_input(2019, 7).unwrap(), Some("3,0,4,0,99\n".to_string()));
+        assert_eq!(store.load_answers(2019, 7).unwrap().part1, Some("5".to_string()));
+        assert_eq!(store.load_answers(2019, 8).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,337 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use disassemble::format_instruction;
+use instruction::{get_instruction_at_index, get_parameters, get_write_parameter, Instruction, ParameterMode};
+use memory::Memory;
+
+#[derive(PartialEq,Debug)]
+pub struct BasicBlock {
+    pub start: usize,
+    pub instructions: Vec<usize>,
+    pub successors: Vec<usize>,
+}
+
+#[derive(PartialEq,Debug)]
+pub struct Analysis {
+    pub instructions: BTreeMap<usize, Instruction>,
+    pub blocks: Vec<BasicBlock>,
+    // Jumps whose target is read from memory rather than encoded in the
+    // instruction, or is negative, so their successors can't be known statically
+    pub indirect_jumps: Vec<usize>,
+    // Reachable addresses that don't decode to an instruction
+    pub invalid: Vec<usize>,
+    // (writing instruction, overwritten instruction) pairs
+    pub self_modifications: Vec<(usize, usize)>,
+}
+
+impl Analysis {
+    pub fn is_modified(&self, address:usize) -> bool {
+        self.self_modifications.iter().any(|&(_, target)| target == address)
+    }
+}
+
+fn is_jump(instruction:&Instruction) -> bool {
+    [5,6].contains(&instruction.code)
+}
+
+// Successors of a single instruction, and whether its jump target is
+// indirect. A jump with an immediate condition only gets the edge it can take.
+fn get_successors(address:usize, instruction:&Instruction) -> (Vec<usize>, bool) {
+    let fallthrough = address + instruction.size;
+
+    if instruction.code == 99 {
+        return (vec![], false);
+    }
+
+    if !is_jump(instruction) {
+        return (vec![fallthrough], false);
+    }
+
+    let (can_jump, can_fall_through) = match instruction.p1_mode {
+        ParameterMode::Immediate => {
+            let jumps = (instruction.p1 != 0) == (instruction.code == 5);
+
+            (jumps, !jumps)
+        },
+        _ => (true, true),
+    };
+
+    let mut successors = vec![];
+    let mut indirect = false;
+
+    // A negative target can't be followed any more than one read from memory
+    if can_jump {
+        if instruction.p2_mode != ParameterMode::Immediate || instruction.p2 < 0 {
+            indirect = true;
+        } else {
+            successors.push(instruction.p2 as usize);
+        }
+    }
+
+    if can_fall_through {
+        successors.push(fallthrough);
+    }
+
+    return (successors, indirect);
+}
+
+// Follows every statically known path from address 0. Writes through
+// relative addresses and jumps through memory can't be resolved, so the
+// result describes the code the program can reach without them.
+pub fn analyze(program:&Vec<i64>) -> Analysis {
+    let memory = Memory::new(program.clone());
+
+    let mut instructions = BTreeMap::new();
+    let mut invalid = BTreeSet::new();
+    let mut indirect_jumps = vec![];
+    let mut leaders = BTreeSet::new();
+    let mut pending = vec![0];
+
+    leaders.insert(0);
+
+    while let Some(address) = pending.pop() {
+        if instructions.contains_key(&address) || invalid.contains(&address) {
+            continue;
+        }
+
+        let instruction = match get_instruction_at_index(address, &memory) {
+            Ok(ref instruction) if address + instruction.size > program.len() => {
+                invalid.insert(address);
+                continue;
+            },
+            Ok(instruction) => instruction,
+            Err(_) => {
+                invalid.insert(address);
+                continue;
+            },
+        };
+
+        let (successors, indirect) = get_successors(address, &instruction);
+
+        if indirect {
+            indirect_jumps.push(address);
+        }
+
+        if is_jump(&instruction) {
+            leaders.extend(successors.iter().cloned());
+        }
+
+        pending.extend(successors);
+        instructions.insert(address, instruction);
+    }
+
+    indirect_jumps.sort();
+
+    let blocks = get_blocks(&instructions, &leaders);
+    let self_modifications = get_self_modifications(&instructions);
+
+    return Analysis {
+        instructions: instructions,
+        blocks: blocks,
+        indirect_jumps: indirect_jumps,
+        invalid: invalid.into_iter().collect(),
+        self_modifications: self_modifications,
+    };
+}
+
+fn get_blocks(instructions:&BTreeMap<usize, Instruction>, leaders:&BTreeSet<usize>) -> Vec<BasicBlock> {
+    let mut blocks: Vec<BasicBlock> = vec![];
+    let mut expected_next = None;
+
+    for (&address, instruction) in instructions {
+        let continues_block = expected_next == Some(address) && !leaders.contains(&address);
+
+        if !continues_block {
+            blocks.push(BasicBlock { start: address, instructions: vec![], successors: vec![] });
+        }
+
+        let block = blocks.last_mut().unwrap();
+        let (successors, _) = get_successors(address, instruction);
+
+        block.instructions.push(address);
+        block.successors = successors;
+
+        expected_next = if is_jump(instruction) || instruction.code == 99 { None } else { Some(address + instruction.size) };
+    }
+
+    return blocks;
+}
+
+fn get_self_modifications(instructions:&BTreeMap<usize, Instruction>) -> Vec<(usize, usize)> {
+    let mut modifications = vec![];
+
+    for (&address, instruction) in instructions {
+        let target = match get_write_parameter(instruction.code) {
+            Some(n) => get_parameters(instruction)[n - 1],
+            None => continue,
+        };
+
+        if *target.0 != ParameterMode::Position || target.1 < 0 {
+            continue;
+        }
+
+        let written = target.1 as usize;
+
+        for (&start, overwritten) in instructions.range(..=written).rev() {
+            if start + 4 <= written {
+                break;
+            }
+
+            if start + overwritten.size > written {
+                modifications.push((address, start));
+            }
+        }
+    }
+
+    modifications.sort();
+
+    return modifications;
+}
+
+fn escape_dot(text:&str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+pub fn format_dot(analysis:&Analysis) -> String {
+    let labels = HashMap::new();
+    let block_starts: BTreeSet<usize> = analysis.blocks.iter().map(|block| block.start).collect();
+
+    let mut output = String::new();
+    let mut missing = BTreeSet::new();
+
+    output.push_str("digraph cfg {\n");
+    output.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+
+    for block in &analysis.blocks {
+        let mut label = String::new();
+        let mut modified = false;
+
+        for address in &block.instructions {
+            let text = format_instruction(&analysis.instructions[address], &labels);
+
+            label.push_str(&format!("{:04}  {}", address, escape_dot(&text)));
+
+            for &(writer, target) in &analysis.self_modifications {
+                if target == *address {
+                    label.push_str(&format!("  ; modified by {:04}", writer));
+                    modified = true;
+                }
+            }
+
+            label.push_str("\\l");
+        }
+
+        let style = if modified { ", style=filled, fillcolor=\"#f4cccc\"" } else { "" };
+
+        output.push_str(&format!("    b{} [label=\"{}\"{}];\n", block.start, label, style));
+
+        for successor in &block.successors {
+            if !block_starts.contains(successor) {
+                missing.insert(*successor);
+            }
+
+            output.push_str(&format!("    b{} -> b{};\n", block.start, successor));
+        }
+
+        if analysis.indirect_jumps.contains(block.instructions.last().unwrap()) {
+            output.push_str(&format!("    b{} -> indirect [style=dashed];\n", block.start));
+        }
+    }
+
+    for address in missing {
+        output.push_str(&format!("    b{} [label=\"{:04}  invalid\", shape=octagon];\n", address, address));
+    }
+
+    if !analysis.indirect_jumps.is_empty() {
+        output.push_str("    indirect [label=\"?\", shape=diamond];\n");
+    }
+
+    output.push_str("}\n");
+
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_straight_line_program() {
+        let analysis = analyze(&vec![1101,2,3,7,4,7,99,0]);
+
+        assert_eq!(analysis.blocks, vec![BasicBlock { start: 0, instructions: vec![0,4,6], successors: vec![] }]);
+        assert!(analysis.indirect_jumps.is_empty());
+        assert!(analysis.invalid.is_empty());
+        assert!(analysis.self_modifications.is_empty());
+    }
+
+    #[test]
+    fn test_blocks_and_edges() {
+        // Counts [100] down, jumping back to 0 until it reaches zero
+        let analysis = analyze(&vec![1001,100,-1,100,1005,100,0,4,100,99]);
+
+        assert_eq!(analysis.blocks, vec![
+            BasicBlock { start: 0, instructions: vec![0,4], successors: vec![0,7] },
+            BasicBlock { start: 7, instructions: vec![7,9], successors: vec![] },
+        ]);
+
+        // The day 5 comparison example, with an unconditional jump over the else branch
+        let analysis = analyze(&vec![3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99]);
+
+        let starts: Vec<usize> = analysis.blocks.iter().map(|block| block.start).collect();
+
+        assert_eq!(starts, vec![0,9,16,22,31,36,46]);
+        assert_eq!(analysis.blocks[2].successors, vec![36]);
+        assert_eq!(analysis.blocks[3].successors, vec![46]);
+        assert!(!analysis.instructions.contains_key(&19));
+    }
+
+    #[test]
+    fn test_indirect_jump() {
+        let analysis = analyze(&vec![3,10,5,9,10,104,1,99,0,12,0,99]);
+
+        assert_eq!(analysis.indirect_jumps, vec![2]);
+        assert_eq!(analysis.blocks[0].successors, vec![5]);
+        assert!(format_dot(&analysis).contains("b0 -> indirect [style=dashed];"));
+
+        let analysis = analyze(&vec![1105,1,-1,99]);
+
+        assert_eq!(analysis.indirect_jumps, vec![0]);
+        assert_eq!(analysis.blocks[0].successors, vec![]);
+        assert!(format_dot(&analysis).contains("b0 -> indirect [style=dashed];"));
+    }
+
+    #[test]
+    fn test_self_modification() {
+        // Input is written over the operand of the output instruction at 2
+        let analysis = analyze(&vec![3,3,104,0,99]);
+
+        assert_eq!(analysis.self_modifications, vec![(0,2)]);
+        assert!(analysis.is_modified(2));
+        assert!(!analysis.is_modified(0));
+
+        let dot = format_dot(&analysis);
+
+        assert!(dot.contains("0002  OUT #0  ; modified by 0000\\l"));
+        assert!(dot.contains("fillcolor"));
+    }
+
+    #[test]
+    fn test_invalid_code() {
+        let analysis = analyze(&vec![1105,1,5,99,0,42]);
+
+        assert_eq!(analysis.invalid, vec![5]);
+        assert!(format_dot(&analysis).contains("b5 [label=\"0005  invalid\", shape=octagon];"));
+
+        let analysis = analyze(&vec![1,0,0,0]);
+
+        assert_eq!(analysis.invalid, vec![4]);
+    }
+
+    #[test]
+    fn test_format_dot() {
+        let dot = format_dot(&analyze(&vec![1001,100,-1,100,1005,100,0,4,100,99]));
+
+        assert_eq!(dot, "digraph cfg {\n    node [shape=box, fontname=\"monospace\"];\n    b0 [label=\"0000  ADD [100], #-1 -> [100]\\l0004  JT [100], #0\\l\"];\n    b0 -> b0;\n    b0 -> b7;\n    b7 [label=\"0007  OUT [100]\\l0009  HLT\\l\"];\n}\n");
+    }
+}
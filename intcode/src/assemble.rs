@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+
+use error::IntcodeError;
+use instruction::{encode_instruction_code, get_write_parameter, Instruction, ParameterMode};
+
+enum Body {
+    Empty,
+    Instruction(i64, Vec<String>),
+    Data(Vec<String>),
+}
+
+struct Statement {
+    line: usize,
+    labels: Vec<String>,
+    body: Body,
+}
+
+pub fn get_opcode(mnemonic:&str) -> Option<i64> {
+    match mnemonic.to_uppercase().as_str() {
+        "ADD" => Some(1),
+        "MUL" => Some(2),
+        "IN" => Some(3),
+        "OUT" => Some(4),
+        "JT" => Some(5),
+        "JF" => Some(6),
+        "LT" => Some(7),
+        "EQ" => Some(8),
+        "ARB" => Some(9),
+        "HLT" => Some(99),
+        _ => None,
+    }
+}
+
+fn get_instruction_size(code:i64) -> usize {
+    match code {
+        1 | 2 | 7 | 8 => 4,
+        5 | 6 => 3,
+        3 | 4 | 9 => 2,
+        _ => 1,
+    }
+}
+
+fn assemble_error(line:usize, message:String) -> IntcodeError {
+    IntcodeError::AssembleError { line: line, message: message }
+}
+
+fn is_label(token:&str) -> bool {
+    match token.chars().next() {
+        Some(c) if c.is_alphabetic() || c == '_' => token.chars().all(|c| c.is_alphanumeric() || c == '_'),
+        _ => false,
+    }
+}
+
+fn split_operands(text:&str) -> Vec<String> {
+    text.replace("->", ",")
+        .split(',')
+        .map(|operand| operand.trim().to_string())
+        .filter(|operand| !operand.is_empty())
+        .collect()
+}
+
+fn parse_statement(line:usize, text:&str) -> Result<Statement, IntcodeError> {
+    let mut text = match text.find(';') {
+        Some(idx) => &text[..idx],
+        None => text,
+    }.trim();
+
+    // Skip the address column emitted by the disassembler
+    let first_token_length = text.find(char::is_whitespace).unwrap_or(text.len());
+
+    if first_token_length > 0 && text[..first_token_length].chars().all(|c| c.is_ascii_digit()) {
+        text = text[first_token_length..].trim();
+    }
+
+    let mut labels = vec![];
+
+    while let Some(idx) = text.find(':') {
+        let label = text[..idx].trim();
+
+        if !is_label(label) {
+            return Err(assemble_error(line, format!("invalid label {:?}", label)));
+        }
+
+        labels.push(label.to_string());
+        text = text[idx + 1..].trim();
+    }
+
+    if text.is_empty() {
+        return Ok(Statement { line: line, labels: labels, body: Body::Empty });
+    }
+
+    let mnemonic_length = text.find(char::is_whitespace).unwrap_or(text.len());
+    let (mnemonic, operands) = (&text[..mnemonic_length], &text[mnemonic_length..]);
+
+    if mnemonic.to_lowercase() == ".data" {
+        return Ok(Statement { line: line, labels: labels, body: Body::Data(split_operands(operands)) });
+    }
+
+    let code = match get_opcode(mnemonic) {
+        Some(code) => code,
+        None => return Err(assemble_error(line, format!("unknown mnemonic {:?}", mnemonic))),
+    };
+
+    let operands = split_operands(operands);
+
+    if operands.len() != get_instruction_size(code) - 1 {
+        return Err(assemble_error(line, format!("{} takes {} operands, found {}", mnemonic.to_uppercase(), get_instruction_size(code) - 1, operands.len())));
+    }
+
+    return Ok(Statement { line: line, labels: labels, body: Body::Instruction(code, operands) });
+}
+
+fn resolve_value(line:usize, token:&str, labels:&HashMap<String,usize>) -> Result<i64, IntcodeError> {
+    if let Ok(value) = token.parse::<i64>() {
+        return Ok(value);
+    }
+
+    match labels.get(token) {
+        Some(&address) => Ok(address as i64),
+        None => Err(assemble_error(line, format!("undefined label {:?}", token))),
+    }
+}
+
+fn parse_operand(line:usize, operand:&str, labels:&HashMap<String,usize>) -> Result<(ParameterMode, i64), IntcodeError> {
+    if let Some(value) = operand.strip_prefix('#') {
+        return Ok((ParameterMode::Immediate, resolve_value(line, value.trim(), labels)?));
+    }
+
+    if operand.starts_with('[') && operand.ends_with(']') {
+        let inner = operand[1..operand.len() - 1].trim();
+
+        if let Some(offset) = inner.strip_prefix("rb") {
+            let offset = offset.trim();
+
+            let value = if offset.is_empty() {
+                0
+            } else if let Some(offset) = offset.strip_prefix('+') {
+                resolve_value(line, offset.trim(), labels)?
+            } else if let Some(offset) = offset.strip_prefix('-') {
+                match resolve_value(line, offset.trim(), labels)?.checked_neg() {
+                    Some(value) => value,
+                    None => return Err(assemble_error(line, format!("relative offset in {:?} is out of range", operand))),
+                }
+            } else {
+                return Err(assemble_error(line, format!("invalid relative operand {:?}", operand)));
+            };
+
+            return Ok((ParameterMode::Relative, value));
+        }
+
+        return Ok((ParameterMode::Position, resolve_value(line, inner, labels)?));
+    }
+
+    return Err(assemble_error(line, format!("invalid operand {:?}", operand)));
+}
+
+pub fn assemble(source:&str) -> Result<Vec<i64>, IntcodeError> {
+    let mut statements = vec![];
+
+    for (i, text) in source.lines().enumerate() {
+        statements.push(parse_statement(i + 1, text)?);
+    }
+
+    let mut labels: HashMap<String,usize> = HashMap::new();
+    let mut address = 0;
+
+    for statement in &statements {
+        for label in &statement.labels {
+            if labels.insert(label.clone(), address).is_some() {
+                return Err(assemble_error(statement.line, format!("duplicate label {:?}", label)));
+            }
+        }
+
+        address = address + match statement.body {
+            Body::Empty => 0,
+            Body::Instruction(code, _) => get_instruction_size(code),
+            Body::Data(ref words) => words.len(),
+        };
+    }
+
+    let mut program = vec![];
+
+    for statement in &statements {
+        match statement.body {
+            Body::Empty => (),
+            Body::Instruction(code, ref operands) => {
+                let mut parameters = vec![];
+
+                for operand in operands {
+                    parameters.push(parse_operand(statement.line, operand, &labels)?);
+                }
+
+                if let Some(n) = get_write_parameter(code) {
+                    if parameters[n - 1].0 == ParameterMode::Immediate {
+                        return Err(assemble_error(statement.line, format!("write operand {:?} cannot be immediate", operands[n - 1])));
+                    }
+                }
+
+                let values: Vec<i64> = parameters.iter().map(|&(_, value)| value).collect();
+
+                let mut modes = parameters.into_iter().map(|(mode, _)| mode);
+
+                let instruction = Instruction {
+                    code: code,
+                    p1_mode: modes.next().unwrap_or(ParameterMode::Position),
+                    p2_mode: modes.next().unwrap_or(ParameterMode::Position),
+                    p3_mode: modes.next().unwrap_or(ParameterMode::Position),
+                    p1: *values.first().unwrap_or(&0),
+                    p2: *values.get(1).unwrap_or(&0),
+                    p3: *values.get(2).unwrap_or(&0),
+                    size: get_instruction_size(code),
+                };
+
+                program.push(encode_instruction_code(&instruction));
+                program.extend(values);
+            },
+            Body::Data(ref words) => {
+                for word in words {
+                    program.push(resolve_value(statement.line, word, &labels)?);
+                }
+            },
+        };
+    }
+
+    return Ok(program);
+}
+
+pub fn format_program(program:&Vec<i64>) -> String {
+    let words: Vec<String> = program.iter().map(|word| word.to_string()).collect();
+
+    return words.join(",");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use disassemble::disassemble;
+    use machine::{get_program_from_string, Intcode, RunState};
+
+    #[test]
+    fn test_assemble() {
+        let source = "\
+            ; doubles its input
+            start: IN -> [value]
+                   MUL [value], #2 -> [value]
+                   OUT [value]
+                   JT #1, #start
+            value: .data 0
+        ";
+
+        let program = assemble(source).unwrap();
+
+        assert_eq!(program, vec![3,11,1002,11,2,11,4,11,1105,1,0,0]);
+
+        let mut machine = Intcode::new(program);
+        machine.push_input(21);
+
+        assert_eq!(machine.run_until_event().unwrap(), RunState::Output(42));
+    }
+
+    #[test]
+    fn test_assemble_relative_operands() {
+        let program = assemble("ARB #10\nADD [rb+1], [rb-2] -> [rb]\nIN -> [rb+3]\nHLT").unwrap();
+
+        assert_eq!(program, vec![109,10,22201,1,-2,0,203,3,99]);
+    }
+
+    #[test]
+    fn test_format_program() {
+        let program = assemble("OUT #7\nHLT").unwrap();
+
+        assert_eq!(format_program(&program), "104,7,99");
+        assert_eq!(get_program_from_string(format_program(&program)).unwrap(), program);
+    }
+
+    #[test]
+    fn test_round_trip_with_disassembler() {
+        let programs = vec![
+            vec![3,9,8,9,10,9,4,9,99,-1,8],
+            vec![3,3,1105,-1,9,1101,0,0,12,4,12,99,1],
+            vec![3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9],
+            vec![109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99],
+            vec![3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99],
+        ];
+
+        for program in programs {
+            assert_eq!(assemble(&disassemble(&program)).unwrap(), program);
+        }
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        assert_eq!(assemble("NOP"), Err(IntcodeError::AssembleError { line: 1, message: "unknown mnemonic \"NOP\"".to_string() }));
+        assert_eq!(assemble("HLT\nADD #1, #2"), Err(IntcodeError::AssembleError { line: 2, message: "ADD takes 3 operands, found 2".to_string() }));
+        assert_eq!(assemble("ADD #1, #2 -> #3"), Err(IntcodeError::AssembleError { line: 1, message: "write operand \"#3\" cannot be immediate".to_string() }));
+        assert_eq!(assemble("JT #1, #nowhere"), Err(IntcodeError::AssembleError { line: 1, message: "undefined label \"nowhere\"".to_string() }));
+        assert_eq!(assemble("a: HLT\na: HLT"), Err(IntcodeError::AssembleError { line: 2, message: "duplicate label \"a\"".to_string() }));
+        assert_eq!(assemble("OUT 5"), Err(IntcodeError::AssembleError { line: 1, message: "invalid operand \"5\"".to_string() }));
+        assert_eq!(assemble("HLT\nOUT [rb--9223372036854775808]"), Err(IntcodeError::AssembleError { line: 2, message: "relative offset in \"[rb--9223372036854775808]\" is out of range".to_string() }));
+    }
+}
@@ -0,0 +1,260 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+// Limbs are stored least significant first in base 10^9, which keeps
+// parsing and printing simple at the cost of slightly slower arithmetic.
+const LIMB_BASE: u64 = 1_000_000_000;
+const LIMB_DIGITS: usize = 9;
+
+#[derive(Clone,PartialEq,Eq,Debug)]
+pub struct BigInt {
+    negative: bool,
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> BigInt {
+        BigInt { negative: false, limbs: vec![] }
+    }
+
+    pub fn from_i64(value:i64) -> BigInt {
+        let mut magnitude = value.unsigned_abs();
+        let mut limbs = vec![];
+
+        while magnitude > 0 {
+            limbs.push((magnitude % LIMB_BASE) as u32);
+            magnitude = magnitude / LIMB_BASE;
+        }
+
+        return BigInt { negative: value < 0, limbs: limbs };
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        let mut magnitude: i128 = 0;
+
+        for &limb in self.limbs.iter().rev() {
+            magnitude = magnitude.checked_mul(LIMB_BASE as i128)?.checked_add(limb as i128)?;
+        }
+
+        let value = if self.negative { -magnitude } else { magnitude };
+
+        if value < i64::MIN as i128 || value > i64::MAX as i128 {
+            return None;
+        }
+
+        return Some(value as i64);
+    }
+
+    pub fn parse(token:&str) -> Option<BigInt> {
+        let (negative, digits) = match token.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, token.strip_prefix('+').unwrap_or(token)),
+        };
+
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let mut limbs = vec![];
+        let mut end = digits.len();
+
+        while end > 0 {
+            let start = end.saturating_sub(LIMB_DIGITS);
+
+            limbs.push(digits[start..end].parse::<u32>().unwrap());
+            end = start;
+        }
+
+        return Some(BigInt::normalized(negative, limbs));
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn add(&self, other:&BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::normalized(self.negative, add_magnitudes(&self.limbs, &other.limbs));
+        }
+
+        match compare_magnitudes(&self.limbs, &other.limbs) {
+            Ordering::Less => BigInt::normalized(other.negative, subtract_magnitudes(&other.limbs, &self.limbs)),
+            _ => BigInt::normalized(self.negative, subtract_magnitudes(&self.limbs, &other.limbs)),
+        }
+    }
+
+    pub fn mul(&self, other:&BigInt) -> BigInt {
+        let mut product = vec![0u64; self.limbs.len() + other.limbs.len()];
+
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let current = product[i + j] + (a as u64) * (b as u64) + carry;
+
+                product[i + j] = current % LIMB_BASE;
+                carry = current / LIMB_BASE;
+            }
+
+            product[i + other.limbs.len()] += carry;
+        }
+
+        let limbs = product.into_iter().map(|limb| limb as u32).collect();
+
+        return BigInt::normalized(self.negative != other.negative, limbs);
+    }
+
+    fn normalized(negative:bool, mut limbs:Vec<u32>) -> BigInt {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+
+        let negative = negative && !limbs.is_empty();
+
+        return BigInt { negative: negative, limbs: limbs };
+    }
+}
+
+fn compare_magnitudes(a:&Vec<u32>, b:&Vec<u32>) -> Ordering {
+    if a.len() != b.len() {
+        return a.len().cmp(&b.len());
+    }
+
+    return a.iter().rev().cmp(b.iter().rev());
+}
+
+fn add_magnitudes(a:&Vec<u32>, b:&Vec<u32>) -> Vec<u32> {
+    let mut sum = vec![];
+    let mut carry = 0;
+
+    for i in 0..a.len().max(b.len()) {
+        let current = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+
+        sum.push((current % LIMB_BASE) as u32);
+        carry = current / LIMB_BASE;
+    }
+
+    if carry > 0 {
+        sum.push(carry as u32);
+    }
+
+    return sum;
+}
+
+// Requires |a| >= |b|.
+fn subtract_magnitudes(a:&Vec<u32>, b:&Vec<u32>) -> Vec<u32> {
+    let mut difference = vec![];
+    let mut borrow = 0;
+
+    for (i, &limb) in a.iter().enumerate() {
+        let mut current = limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+
+        borrow = 0;
+
+        if current < 0 {
+            current = current + LIMB_BASE as i64;
+            borrow = 1;
+        }
+
+        difference.push(current as u32);
+    }
+
+    return difference;
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other:&BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.limbs, &other.limbs),
+            (true, true) => compare_magnitudes(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other:&BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.limbs.is_empty() {
+            return write!(f, "0");
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+
+        write!(f, "{}", self.limbs[self.limbs.len() - 1])?;
+
+        for limb in self.limbs.iter().rev().skip(1) {
+            write!(f, "{:09}", limb)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(token:&str) -> BigInt {
+        BigInt::parse(token).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        for token in ["0", "7", "-7", "1000000000", "-123456789012345678901234567890"].iter() {
+            assert_eq!(big(token).to_string(), *token);
+        }
+
+        assert_eq!(big("-0"), BigInt::zero());
+        assert_eq!(big("+0012").to_string(), "12");
+        assert_eq!(BigInt::parse(""), None);
+        assert_eq!(BigInt::parse("-"), None);
+        assert_eq!(BigInt::parse("12a"), None);
+    }
+
+    #[test]
+    fn test_i64_conversion() {
+        for &value in [0, 1, -1, 999999999, 1000000000, i64::MAX, i64::MIN].iter() {
+            assert_eq!(BigInt::from_i64(value).to_i64(), Some(value));
+            assert_eq!(BigInt::from_i64(value).to_string(), value.to_string());
+        }
+
+        assert_eq!(big("9223372036854775808").to_i64(), None);
+        assert_eq!(big("-9223372036854775809").to_i64(), None);
+    }
+
+    #[test]
+    fn test_add() {
+        assert_eq!(big("999999999").add(&big("1")), big("1000000000"));
+        assert_eq!(big("5").add(&big("-8")), big("-3"));
+        assert_eq!(big("-5").add(&big("8")), big("3"));
+        assert_eq!(big("-5").add(&big("5")), BigInt::zero());
+        assert_eq!(big("1000000000000000000").add(&big("-1")), big("999999999999999999"));
+        assert_eq!(big("9223372036854775807").add(&big("1")), big("9223372036854775808"));
+    }
+
+    #[test]
+    fn test_mul() {
+        assert_eq!(big("34915192").mul(&big("34915192")), big("1219070632396864"));
+        assert_eq!(big("-3").mul(&big("4")), big("-12"));
+        assert_eq!(big("-3").mul(&big("-4")), big("12"));
+        assert_eq!(big("-3").mul(&BigInt::zero()), BigInt::zero());
+        assert_eq!(big("9223372036854775807").mul(&big("9223372036854775807")), big("85070591730234615847396907784232501249"));
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(big("-10") < big("-9"));
+        assert!(big("-1") < BigInt::zero());
+        assert!(big("1000000000") > big("999999999"));
+        assert!(big("123456789012345678901") > big("123456789012345678900"));
+    }
+}
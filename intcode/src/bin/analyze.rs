@@ -0,0 +1,42 @@
+extern crate intcode;
+
+use std::env;
+use std::io;
+
+use intcode::{analyze, format_dot, get_program_from_string};
+
+fn main() -> io::Result<()> {
+    let mut input = String::new();
+
+    io::stdin().read_line(&mut input)?;
+
+    let program = get_program_from_string(input.trim_end().to_string())?;
+
+    let analysis = analyze(&program);
+
+    // Pass --dot to print the control-flow graph for Graphviz instead of a summary
+    if env::args().any(|arg| arg == "--dot") {
+        print!("{}", format_dot(&analysis));
+        return Ok(());
+    }
+
+    for block in &analysis.blocks {
+        let successors: Vec<String> = block.successors.iter().map(|s| format!("{:04}", s)).collect();
+
+        println!("block {:04}: {} instructions -> {}", block.start, block.instructions.len(), successors.join(", "));
+    }
+
+    for address in &analysis.indirect_jumps {
+        println!("indirect jump at {:04}", address);
+    }
+
+    for address in &analysis.invalid {
+        println!("invalid code reached at {:04}", address);
+    }
+
+    for &(writer, target) in &analysis.self_modifications {
+        println!("{:04} overwrites instruction at {:04}", writer, target);
+    }
+
+    Ok(())
+}
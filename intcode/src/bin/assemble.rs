@@ -0,0 +1,18 @@
+extern crate intcode;
+
+use std::io;
+use std::io::prelude::*;
+
+use intcode::{assemble, format_program};
+
+fn main() -> io::Result<()> {
+    let mut source = String::new();
+
+    io::stdin().read_to_string(&mut source)?;
+
+    let program = assemble(&source)?;
+
+    println!("{}", format_program(&program));
+
+    Ok(())
+}
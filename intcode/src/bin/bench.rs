@@ -0,0 +1,81 @@
+extern crate intcode;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::time::{Duration, Instant};
+
+use intcode::{get_program_from_string, CompiledIntcode, Intcode, IntcodeError};
+
+const RUNS: usize = 5;
+
+// Used when no program is given: the day 9 BOOST program from the puzzle
+// input store, run in sensor boost mode
+const BOOST_PATH: &str = "inputs/2019/09.txt";
+const BOOST_INPUT: i64 = 2;
+
+// Best of several runs, along with the outputs of the last one.
+fn time_runs<F: Fn() -> Result<Vec<i64>, IntcodeError>>(run:F) -> Result<(Duration, Vec<i64>), IntcodeError> {
+    let mut best = None;
+    let mut outputs = vec![];
+
+    for _ in 0..RUNS {
+        let start = Instant::now();
+
+        outputs = run()?;
+
+        let elapsed = start.elapsed();
+
+        best = Some(best.map_or(elapsed, |b:Duration| b.min(elapsed)));
+    }
+
+    return Ok((best.unwrap(), outputs));
+}
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+
+    let path = args.get(1).map_or(BOOST_PATH, |path| path.as_str());
+
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) => return Err(io::Error::new(error.kind(), format!("{}: {}", path, error))),
+    };
+
+    let program = get_program_from_string(text.trim_end().to_string())?;
+    let mut inputs = if args.len() > 1 { vec![] } else { vec![BOOST_INPUT] };
+
+    for input in args.iter().skip(2) {
+        match input.parse::<i64>() {
+            Ok(value) => inputs.push(value),
+            Err(_) => return Err(IntcodeError::ParseError { token: input.to_string() }.into()),
+        };
+    }
+
+    let (interpreted, interpreted_outputs) = time_runs(|| {
+        let mut outputs = vec![];
+        Intcode::new(program.clone()).run(&mut inputs.clone(), &mut outputs)?;
+        Ok(outputs)
+    })?;
+
+    let (compiled, compiled_outputs) = time_runs(|| {
+        let mut outputs = vec![];
+        CompiledIntcode::new(program.clone()).run(&mut inputs.clone(), &mut outputs)?;
+        Ok(outputs)
+    })?;
+
+    if interpreted_outputs != compiled_outputs {
+        return Err(io::Error::other(format!("outputs differ: interpreter {:?}, compiled {:?}", interpreted_outputs, compiled_outputs)));
+    }
+
+    println!("outputs: {:?}", compiled_outputs);
+    println!("interpreter: {:?}", interpreted);
+    println!("compiled:    {:?}", compiled);
+    println!("speedup:     {:.2}x", interpreted.as_secs_f64() / compiled.as_secs_f64());
+
+    if compiled >= interpreted {
+        return Err(io::Error::other("compiled run is not faster than the interpreter"));
+    }
+
+    Ok(())
+}
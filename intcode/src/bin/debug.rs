@@ -0,0 +1,48 @@
+extern crate intcode;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::io::Write;
+
+use intcode::{get_program_from_string, Debugger, IntcodeError};
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() < 2 {
+        println!("usage: {} <program file> [--max-steps N]", args[0]);
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid arguments"));
+    }
+
+    let program = get_program_from_string(fs::read_to_string(&args[1])?.trim_end().to_string())?;
+
+    let mut debugger = Debugger::new(program);
+
+    if args.len() > 2 {
+        debugger.max_steps = match (args[2].as_str(), args.get(3)) {
+            ("--max-steps", Some(value)) => value.parse::<u64>().map_err(|_| IntcodeError::ParseError { token: value.to_string() })?,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("unexpected argument {:?}", args[2]))),
+        };
+    }
+
+    println!("{}", debugger.describe_current_instruction());
+
+    loop {
+        io::stdout().write_all(b"(icdb) ")?;
+        io::stdout().flush()?;
+
+        let mut command = String::new();
+
+        if io::stdin().read_line(&mut command)? == 0 {
+            break;
+        }
+
+        match debugger.execute_command(&command) {
+            Some(response) => println!("{}", response),
+            None => break,
+        };
+    }
+
+    Ok(())
+}
@@ -0,0 +1,17 @@
+extern crate intcode;
+
+use std::io;
+
+use intcode::{disassemble, get_program_from_string};
+
+fn main() -> io::Result<()> {
+    let mut input = String::new();
+
+    io::stdin().read_line(&mut input)?;
+
+    let program = get_program_from_string(input.trim_end().to_string())?;
+
+    print!("{}", disassemble(&program));
+
+    Ok(())
+}
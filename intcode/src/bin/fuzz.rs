@@ -0,0 +1,60 @@
+extern crate intcode;
+
+use std::env;
+use std::io;
+
+use intcode::{fuzz, BigInt, CompiledIntcode, Execution, Finding, Intcode, IntcodeError};
+
+fn format_execution(execution:&Execution) -> String {
+    format!("{:?}, outputs {:?}", execution.outcome, execution.outputs)
+}
+
+fn report(name:&str, finding:&Option<Finding>) -> bool {
+    let finding = match *finding {
+        Some(ref finding) => finding,
+        None => {
+            println!("{}: no differences", name);
+            return false;
+        },
+    };
+
+    let program: Vec<String> = finding.program.iter().map(|w| w.to_string()).collect();
+
+    println!("{}: difference found from seed {}", name, finding.seed);
+    println!("  program:   {}", program.join(","));
+    println!("  inputs:    {:?}", finding.inputs);
+    println!("  reference: {}", format_execution(&finding.expected));
+    println!("  variant:   {}", format_execution(&finding.actual));
+
+    let addresses = finding.expected.memory.iter().zip(finding.actual.memory.iter()).enumerate();
+
+    for (address, (expected, actual)) in addresses.filter(|&(_, (a, b))| a != b) {
+        println!("  [{}]: {:?} != {:?}", address, expected, actual);
+    }
+
+    return true;
+}
+
+fn parse_arg(arg:Option<&String>, default:u64) -> io::Result<u64> {
+    match arg {
+        Some(arg) => arg.parse::<u64>().map_err(|_| IntcodeError::ParseError { token: arg.to_string() }.into()),
+        None => Ok(default),
+    }
+}
+
+// Usage: fuzz [seed] [iterations]
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+
+    let seed = parse_arg(args.get(1), 0)?;
+    let iterations = parse_arg(args.get(2), 1000)?;
+
+    let compiled = report("compiled", &fuzz::<Intcode, CompiledIntcode>(seed, iterations));
+    let big = report("bigint", &fuzz::<Intcode, Intcode<BigInt>>(seed, iterations));
+
+    if compiled || big {
+        return Err(io::Error::other("implementations disagree"));
+    }
+
+    Ok(())
+}
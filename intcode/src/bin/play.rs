@@ -0,0 +1,30 @@
+extern crate intcode;
+
+use std::env;
+use std::fs;
+use std::io;
+
+use intcode::{get_program_from_string, run_with_io, Budget, Intcode, IoMode};
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() < 2 {
+        println!("usage: {} <program file> [--numeric] [--max-instructions N] [--max-memory N] [--timeout-ms N]", args[0]);
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid arguments"));
+    }
+
+    let program = get_program_from_string(fs::read_to_string(&args[1])?.trim_end().to_string())?;
+
+    let mode = if args.iter().any(|arg| arg == "--numeric") { IoMode::Numeric } else { IoMode::Ascii };
+
+    let mut budget = Budget::from_args(&args)?;
+    let mut machine = Intcode::new(program);
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+
+    run_with_io(&mut machine, &mut budget, mode, &mut stdin.lock(), &mut stdout.lock())?;
+
+    Ok(())
+}
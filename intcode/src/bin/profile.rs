@@ -0,0 +1,44 @@
+extern crate intcode;
+
+use std::env;
+use std::fs;
+use std::io;
+
+use intcode::{get_program_from_string, Intcode, IntcodeError, Profiler, RunState};
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() < 2 {
+        println!("usage: {} <program file> [input...]", args[0]);
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid arguments"));
+    }
+
+    let program = get_program_from_string(fs::read_to_string(&args[1])?.trim_end().to_string())?;
+
+    let mut machine = Intcode::new(program.clone());
+    let mut profiler = Profiler::new();
+
+    for input in &args[2..] {
+        match input.parse::<i64>() {
+            Ok(value) => machine.push_input(value),
+            Err(_) => return Err(IntcodeError::ParseError { token: input.to_string() }.into()),
+        };
+    }
+
+    loop {
+        match profiler.run_until_event(&mut machine)? {
+            RunState::Output(out) => println!("output: {}", out),
+            RunState::NeedsInput => {
+                eprintln!("program needs more input at pc {}", machine.pc);
+                break;
+            },
+            RunState::Halted => break,
+        };
+    }
+
+    println!();
+    print!("{}", profiler.format_report(&program));
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+extern crate intcode;
+
+use std::env;
+use std::fs;
+use std::io;
+
+use intcode::{execute_symbolic, get_program_from_string, SymbolicPath};
+
+const MAX_STEPS: usize = 100000;
+
+// Runs the program with the named memory cells and inputs left symbolic and
+// prints, for every path, the conditions it took, its outputs and the value
+// left at the chosen address (0 by default).
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() < 2 {
+        println!("usage: {} <program file> [address=name ...] [input=name ...] [--address N]", args[0]);
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid arguments"));
+    }
+
+    let program = get_program_from_string(fs::read_to_string(&args[1])?.trim_end().to_string())?;
+
+    let mut symbols = vec![];
+    let mut inputs = vec![];
+    let mut address = 0;
+
+    let mut rest = args[2..].iter();
+
+    while let Some(arg) = rest.next() {
+        if arg == "--address" {
+            address = match rest.next().map(|value| value.parse::<usize>()) {
+                Some(Ok(value)) => value,
+                _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "--address needs a number")),
+            };
+
+            continue;
+        }
+
+        let (key, name) = match arg.find('=') {
+            Some(i) => (&arg[..i], &arg[i + 1..]),
+            None => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("expected address=name, got {:?}", arg))),
+        };
+
+        if key == "input" {
+            inputs.push(name);
+            continue;
+        }
+
+        match key.parse::<usize>() {
+            Ok(cell) => symbols.push((cell, name)),
+            Err(_) => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("invalid address {:?}", key))),
+        };
+    }
+
+    let paths = execute_symbolic(SymbolicPath::new(&program, &symbols, &inputs), MAX_STEPS);
+
+    for (i, &(ref path, ref end)) in paths.iter().enumerate() {
+        println!("path {}: {:?}", i, end);
+
+        for condition in &path.conditions {
+            println!("  if   {}", condition);
+        }
+
+        for output in &path.outputs {
+            println!("  out  {}", output);
+        }
+
+        println!("  [{}] = {}", address, path.read(address));
+    }
+
+    Ok(())
+}
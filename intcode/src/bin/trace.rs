@@ -0,0 +1,75 @@
+extern crate intcode;
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+
+use intcode::{format_instruction, get_program_from_string, replay_trace, Intcode, IntcodeError, ReplayResult, RunState, TraceRecorder};
+
+fn read_program(path:&str) -> io::Result<Vec<i64>> {
+    Ok(get_program_from_string(fs::read_to_string(path)?.trim_end().to_string())?)
+}
+
+fn record(path:&str, inputs:&[String]) -> io::Result<()> {
+    let mut machine = Intcode::new(read_program(path)?);
+
+    for input in inputs {
+        match input.parse::<i64>() {
+            Ok(value) => machine.push_input(value),
+            Err(_) => return Err(IntcodeError::ParseError { token: input.to_string() }.into()),
+        };
+    }
+
+    let stdout = io::stdout();
+    let mut recorder = TraceRecorder::new(stdout.lock());
+
+    loop {
+        match recorder.run_until_event(&mut machine)? {
+            RunState::Output(_) => (),
+            RunState::NeedsInput => {
+                eprintln!("program needs more input at pc {}", machine.pc);
+                break;
+            },
+            RunState::Halted => break,
+        };
+    }
+
+    Ok(())
+}
+
+fn replay(path:&str, trace_path:&str) -> io::Result<()> {
+    let trace = fs::read_to_string(trace_path)?;
+
+    match replay_trace(read_program(path)?, &trace)? {
+        ReplayResult::Match { steps } => println!("replayed {} steps without divergence", steps),
+        ReplayResult::Divergence { step, expected, actual } => {
+            println!("divergence at step {} (pc {})", step, expected.pc);
+            println!("expected: {}  {}", format_instruction(&expected.instruction, &HashMap::new()), expected.to_json());
+
+            match actual {
+                Some(event) => println!("actual:   {}  {}", format_instruction(&event.instruction, &HashMap::new()), event.to_json()),
+                None => println!("actual:   program stopped"),
+            };
+        },
+    };
+
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() >= 3 && args[1] == "record" {
+        return record(&args[2], &args[3..]);
+    }
+
+    if args.len() == 4 && args[1] == "replay" {
+        return replay(&args[2], &args[3]);
+    }
+
+    println!("usage: {} record <program file> [input...]", args[0]);
+    println!("       {} replay <program file> <trace file>", args[0]);
+
+    Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid arguments"))
+}
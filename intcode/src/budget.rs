@@ -0,0 +1,304 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use error::IntcodeError;
+use io::{IntcodeInput, IntcodeOutput};
+use machine::{Intcode, RunState};
+use word::Word;
+
+// Reading the clock on every instruction would dominate tight loops, so the
+// deadline is only checked this often.
+const DEADLINE_CHECK_INTERVAL: u64 = 1024;
+
+#[derive(Clone,Copy,PartialEq,Debug)]
+pub enum BudgetLimit {
+    Instructions(u64),
+    MemoryGrowth(usize),
+    Timeout(Duration),
+}
+
+impl fmt::Display for BudgetLimit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BudgetLimit::Instructions(max) => write!(f, "{} instructions", max),
+            BudgetLimit::MemoryGrowth(max) => write!(f, "{} words of memory growth", max),
+            BudgetLimit::Timeout(timeout) => write!(f, "{}ms", timeout.as_millis()),
+        }
+    }
+}
+
+// Limits on a run, along with what has been used so far. The clock and the
+// memory baseline start with the first instruction executed, so a budget
+// should be created per run (or reset) rather than shared between machines.
+#[derive(Clone,PartialEq,Debug,Default)]
+pub struct Budget {
+    pub max_instructions: Option<u64>,
+    pub max_memory_growth: Option<usize>,
+    pub timeout: Option<Duration>,
+    pub instructions: u64,
+    started: Option<(Instant, usize)>,
+}
+
+fn parse_limit(flag:&str, value:Option<&String>) -> Result<u64, IntcodeError> {
+    match value {
+        Some(value) => match value.parse::<u64>() {
+            Ok(limit) => Ok(limit),
+            Err(_) => Err(IntcodeError::ParseError { token: value.to_string() }),
+        },
+        None => Err(IntcodeError::ParseError { token: flag.to_string() }),
+    }
+}
+
+impl Budget {
+    pub fn unlimited() -> Budget {
+        Budget::default()
+    }
+
+    pub fn with_max_instructions(mut self, max:u64) -> Budget {
+        self.max_instructions = Some(max);
+        self
+    }
+
+    pub fn with_max_memory_growth(mut self, max:usize) -> Budget {
+        self.max_memory_growth = Some(max);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout:Duration) -> Budget {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    // Builds a budget from --max-instructions N, --max-memory N and
+    // --timeout-ms N command line arguments. Other arguments are ignored.
+    pub fn from_args(args:&[String]) -> Result<Budget, IntcodeError> {
+        let mut budget = Budget::unlimited();
+
+        for (i, arg) in args.iter().enumerate() {
+            match arg.as_str() {
+                "--max-instructions" => budget = budget.with_max_instructions(parse_limit(arg, args.get(i + 1))?),
+                "--max-memory" => budget = budget.with_max_memory_growth(parse_limit(arg, args.get(i + 1))? as usize),
+                "--timeout-ms" => budget = budget.with_timeout(Duration::from_millis(parse_limit(arg, args.get(i + 1))?)),
+                _ => (),
+            }
+        }
+
+        return Ok(budget);
+    }
+
+    // Forgets what has been used, keeping the limits.
+    pub fn reset(&mut self) {
+        self.instructions = 0;
+        self.started = None;
+    }
+
+    fn exceeded<W: Word>(&self, machine:&Intcode<W>, limit:BudgetLimit) -> IntcodeError {
+        IntcodeError::BudgetExceeded {
+            pc: machine.pc,
+            limit: limit,
+            instructions: self.instructions,
+            memory: machine.memory.size(),
+        }
+    }
+
+    // Refuses to run the next instruction if that would go over the budget.
+    // Nothing is executed when a limit is hit, so a machine stopped by the
+    // budget can be resumed with a larger one.
+    pub fn step<W: Word>(&mut self, machine:&mut Intcode<W>) -> Result<Option<RunState<W>>, IntcodeError> {
+        let (start, initial_memory) = match self.started {
+            Some(started) => started,
+            None => {
+                let started = (Instant::now(), machine.memory.size());
+
+                self.started = Some(started);
+
+                started
+            },
+        };
+
+        // Halting or waiting for input doesn't use any of the budget
+        let pending = match machine.peek_instruction()? {
+            Some(pending) => pending,
+            None => return machine.step(),
+        };
+
+        if let Some(max) = self.max_instructions {
+            if self.instructions >= max {
+                return Err(self.exceeded(machine, BudgetLimit::Instructions(max)));
+            }
+        }
+
+        if let Some(max) = self.max_memory_growth {
+            let size = match pending.write_address() {
+                Some(address) => machine.memory.size_after_write(address),
+                None => machine.memory.size(),
+            };
+
+            if size.saturating_sub(initial_memory) > max {
+                return Err(self.exceeded(machine, BudgetLimit::MemoryGrowth(max)));
+            }
+        }
+
+        if let Some(timeout) = self.timeout {
+            if self.instructions.is_multiple_of(DEADLINE_CHECK_INTERVAL) && start.elapsed() > timeout {
+                return Err(self.exceeded(machine, BudgetLimit::Timeout(timeout)));
+            }
+        }
+
+        let state = machine.step()?;
+
+        self.instructions = self.instructions + 1;
+
+        return Ok(state);
+    }
+
+    pub fn run_until_event<W: Word>(&mut self, machine:&mut Intcode<W>) -> Result<RunState<W>, IntcodeError> {
+        loop {
+            if let Some(state) = self.step(machine)? {
+                return Ok(state);
+            }
+        }
+    }
+
+    pub fn run<W: Word>(&mut self, machine:&mut Intcode<W>, input: &mut dyn IntcodeInput<W>, output: &mut dyn IntcodeOutput<W>) -> Result<(), IntcodeError> {
+        loop {
+            match self.run_until_event(machine)? {
+                RunState::Halted => break,
+                RunState::NeedsInput => match input.read_input()? {
+                    Some(value) => machine.push_input(value),
+                    None => return Err(IntcodeError::InputExhausted { pc: machine.pc }),
+                },
+                RunState::Output(out) => output.write_output(out)?,
+            };
+        }
+
+        return Ok(());
+    }
+}
+
+// execute_program, stopping with BudgetExceeded instead of running forever.
+pub fn execute_program_with_budget(program:Vec<i64>, input: &mut dyn IntcodeInput, output: &mut dyn IntcodeOutput, budget:&mut Budget) -> Result<Vec<i64>, IntcodeError> {
+    let mut machine = Intcode::new(program);
+
+    budget.run(&mut machine, input, output)?;
+
+    return Ok(machine.memory.contiguous);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Jumps to itself forever
+    fn infinite_loop() -> Vec<i64> {
+        vec![1105,1,0]
+    }
+
+    #[test]
+    fn test_unlimited_budget() {
+        let mut outputs = vec![];
+        let memory = execute_program_with_budget(vec![1101,2,3,5,4,5,99], &mut vec![], &mut outputs, &mut Budget::unlimited()).unwrap();
+
+        assert_eq!(memory[5], 5);
+        assert_eq!(outputs, vec![5]);
+    }
+
+    #[test]
+    fn test_instruction_limit() {
+        let mut budget = Budget::unlimited().with_max_instructions(1000);
+        let result = execute_program_with_budget(infinite_loop(), &mut vec![], &mut vec![], &mut budget);
+
+        assert_eq!(result, Err(IntcodeError::BudgetExceeded { pc: 0, limit: BudgetLimit::Instructions(1000), instructions: 1000, memory: 3 }));
+
+        // Halting exactly on the limit is fine
+        let mut budget = Budget::unlimited().with_max_instructions(2);
+
+        assert!(execute_program_with_budget(vec![104,1,104,2,99], &mut vec![], &mut vec![], &mut budget).is_ok());
+    }
+
+    #[test]
+    fn test_resume_after_budget_exceeded() {
+        let mut machine = Intcode::new(vec![104,1,104,2,99]);
+        let mut budget = Budget::unlimited().with_max_instructions(1);
+
+        assert_eq!(budget.run_until_event(&mut machine).unwrap(), RunState::Output(1));
+        assert!(budget.run_until_event(&mut machine).is_err());
+        assert_eq!(machine.pc, 2);
+
+        budget.max_instructions = Some(2);
+
+        assert_eq!(budget.run_until_event(&mut machine).unwrap(), RunState::Output(2));
+        assert_eq!(budget.run_until_event(&mut machine).unwrap(), RunState::Halted);
+    }
+
+    #[test]
+    fn test_memory_limit() {
+        // Writes to ever higher addresses through the relative base
+        let program = vec![109,1,21101,0,7,8,1105,1,0];
+        let mut budget = Budget::unlimited().with_max_memory_growth(10);
+
+        match execute_program_with_budget(program, &mut vec![], &mut vec![], &mut budget) {
+            Err(IntcodeError::BudgetExceeded { limit, memory, .. }) => {
+                assert_eq!(limit, BudgetLimit::MemoryGrowth(10));
+                assert_eq!(memory, 9 + 10);
+            },
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        // A single write far past the end is stopped before it happens
+        let mut budget = Budget::unlimited().with_max_memory_growth(10);
+
+        match execute_program_with_budget(vec![1101,0,7,1000,99], &mut vec![], &mut vec![], &mut budget) {
+            Err(IntcodeError::BudgetExceeded { pc, memory, .. }) => {
+                assert_eq!(pc, 0);
+                assert_eq!(memory, 5);
+            },
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_timeout() {
+        let mut budget = Budget::unlimited().with_timeout(Duration::from_millis(20));
+
+        match execute_program_with_budget(infinite_loop(), &mut vec![], &mut vec![], &mut budget) {
+            Err(IntcodeError::BudgetExceeded { limit, instructions, .. }) => {
+                assert_eq!(limit, BudgetLimit::Timeout(Duration::from_millis(20)));
+                assert!(instructions > 0);
+            },
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_waiting_for_input_is_not_counted() {
+        let mut machine = Intcode::new(vec![3,5,4,5,99,0]);
+        let mut budget = Budget::unlimited().with_max_instructions(2);
+
+        assert_eq!(budget.run_until_event(&mut machine).unwrap(), RunState::NeedsInput);
+        assert_eq!(budget.instructions, 0);
+
+        machine.push_input(4);
+
+        assert_eq!(budget.run_until_event(&mut machine).unwrap(), RunState::Output(4));
+        assert_eq!(budget.run_until_event(&mut machine).unwrap(), RunState::Halted);
+    }
+
+    #[test]
+    fn test_from_args() {
+        let args: Vec<String> = vec!["play", "x.txt", "--max-instructions", "500", "--timeout-ms", "20"].into_iter().map(String::from).collect();
+
+        assert_eq!(Budget::from_args(&args).unwrap(), Budget::unlimited().with_max_instructions(500).with_timeout(Duration::from_millis(20)));
+
+        let args: Vec<String> = vec!["--max-memory".to_string()];
+
+        assert_eq!(Budget::from_args(&args), Err(IntcodeError::ParseError { token: "--max-memory".to_string() }));
+    }
+
+    #[test]
+    fn test_display() {
+        let error = IntcodeError::BudgetExceeded { pc: 4, limit: BudgetLimit::Instructions(10), instructions: 10, memory: 20 };
+
+        assert_eq!(error.to_string(), "budget of 10 instructions exceeded at pc 4 after 10 instructions");
+    }
+}
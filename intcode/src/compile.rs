@@ -0,0 +1,364 @@
+use error::IntcodeError;
+use instruction::{get_instruction_at_index, ParameterMode};
+use io::{IntcodeInput, IntcodeOutput};
+use machine::{Intcode, RunState};
+use memory::Memory;
+
+#[derive(Clone,Copy,PartialEq,Debug)]
+enum Operand {
+    Immediate(i64),
+    Position(usize),
+    Relative(i64),
+}
+
+#[derive(Clone,Copy,PartialEq,Debug)]
+enum Op {
+    Add(Operand, Operand, Operand),
+    Multiply(Operand, Operand, Operand),
+    Input(Operand),
+    Output(Operand),
+    JumpIfTrue(Operand, Operand),
+    JumpIfFalse(Operand, Operand),
+    LessThan(Operand, Operand, Operand),
+    Equals(Operand, Operand, Operand),
+    AdjustBase(Operand),
+    Halt,
+}
+
+fn compile_operand(mode:&ParameterMode, value:i64) -> Option<Operand> {
+    match *mode {
+        ParameterMode::Immediate => Some(Operand::Immediate(value)),
+        ParameterMode::Position if value >= 0 => Some(Operand::Position(value as usize)),
+        ParameterMode::Position => None,
+        ParameterMode::Relative => Some(Operand::Relative(value)),
+    }
+}
+
+fn compile_write_operand(mode:&ParameterMode, value:i64) -> Option<Operand> {
+    match *mode {
+        ParameterMode::Immediate => None,
+        _ => compile_operand(mode, value),
+    }
+}
+
+// Instructions that would fault when executed aren't compiled; the machine
+// falls back to the interpreter for them so errors are reported identically.
+fn compile_instruction(pc:usize, memory:&Memory) -> Option<Op> {
+    let i = get_instruction_at_index(pc, memory).ok()?;
+
+    let op = match i.code {
+        1 => Op::Add(compile_operand(&i.p1_mode, i.p1)?, compile_operand(&i.p2_mode, i.p2)?, compile_write_operand(&i.p3_mode, i.p3)?),
+        2 => Op::Multiply(compile_operand(&i.p1_mode, i.p1)?, compile_operand(&i.p2_mode, i.p2)?, compile_write_operand(&i.p3_mode, i.p3)?),
+        3 => Op::Input(compile_write_operand(&i.p1_mode, i.p1)?),
+        4 => Op::Output(compile_operand(&i.p1_mode, i.p1)?),
+        5 => Op::JumpIfTrue(compile_operand(&i.p1_mode, i.p1)?, compile_operand(&i.p2_mode, i.p2)?),
+        6 => Op::JumpIfFalse(compile_operand(&i.p1_mode, i.p1)?, compile_operand(&i.p2_mode, i.p2)?),
+        7 => Op::LessThan(compile_operand(&i.p1_mode, i.p1)?, compile_operand(&i.p2_mode, i.p2)?, compile_write_operand(&i.p3_mode, i.p3)?),
+        8 => Op::Equals(compile_operand(&i.p1_mode, i.p1)?, compile_operand(&i.p2_mode, i.p2)?, compile_write_operand(&i.p3_mode, i.p3)?),
+        9 => Op::AdjustBase(compile_operand(&i.p1_mode, i.p1)?),
+        99 => Op::Halt,
+        _ => return None,
+    };
+
+    return Some(op);
+}
+
+// An Intcode machine that decodes each instruction once and caches the
+// result by address. Any write that lands on a cached instruction discards
+// it, so self-modifying programs behave exactly as in the interpreter.
+pub struct CompiledIntcode {
+    machine: Intcode,
+    cache: Vec<Option<Op>>,
+}
+
+impl CompiledIntcode {
+    pub fn new(program:Vec<i64>) -> CompiledIntcode {
+        CompiledIntcode::from_machine(Intcode::new(program))
+    }
+
+    pub fn from_machine(machine:Intcode) -> CompiledIntcode {
+        CompiledIntcode {
+            machine: machine,
+            cache: vec![],
+        }
+    }
+
+    pub fn machine(&self) -> &Intcode {
+        &self.machine
+    }
+
+    pub fn into_machine(self) -> Intcode {
+        self.machine
+    }
+
+    pub fn read(&self, address:usize) -> i64 {
+        self.machine.read(address)
+    }
+
+    pub fn write(&mut self, address:usize, value:i64) {
+        self.machine.write(address, value);
+
+        // An instruction is at most four words long
+        for start in address.saturating_sub(3)..address + 1 {
+            if start < self.cache.len() {
+                self.cache[start] = None;
+            }
+        }
+    }
+
+    pub fn push_input(&mut self, value:i64) {
+        self.machine.push_input(value);
+    }
+
+    // Code in contiguous memory is cached; code anywhere else is decoded on
+    // every execution.
+    fn get_op(&mut self, pc:usize) -> Option<Op> {
+        if let Some(&Some(op)) = self.cache.get(pc) {
+            return Some(op);
+        }
+
+        let length = self.machine.memory.contiguous.len();
+
+        if pc >= length {
+            return compile_instruction(pc, &self.machine.memory);
+        }
+
+        if self.cache.len() < length {
+            self.cache.resize(length, None);
+        }
+
+        if self.cache[pc].is_none() {
+            self.cache[pc] = compile_instruction(pc, &self.machine.memory);
+        }
+
+        return self.cache[pc];
+    }
+
+    fn get_address(&self, pc:usize, operand:Operand) -> Result<usize, IntcodeError> {
+        match operand {
+            Operand::Position(address) => Ok(address),
+            Operand::Relative(offset) => match self.machine.base.checked_add(offset) {
+                Some(address) if address < 0 => Err(IntcodeError::NegativeAddress { pc: pc, address: address }),
+                Some(address) => Ok(address as usize),
+                None => Err(IntcodeError::Overflow { pc: pc }),
+            },
+            Operand::Immediate(_) => Err(IntcodeError::ImmediateModeWrite { pc: pc }),
+        }
+    }
+
+    fn load(&self, pc:usize, operand:Operand) -> Result<i64, IntcodeError> {
+        match operand {
+            Operand::Immediate(value) => Ok(value),
+            _ => Ok(self.machine.memory.read(self.get_address(pc, operand)?)),
+        }
+    }
+
+    fn jump_target(pc:usize, target:i64) -> Result<usize, IntcodeError> {
+        if target < 0 {
+            return Err(IntcodeError::NegativeAddress { pc: pc, address: target });
+        }
+
+        return Ok(target as usize);
+    }
+
+    fn arithmetic<F: Fn(i64, i64) -> Option<i64>>(&mut self, pc:usize, a:Operand, b:Operand, c:Operand, operation:F) -> Result<(), IntcodeError> {
+        let x = self.load(pc, a)?;
+        let y = self.load(pc, b)?;
+        let address = self.get_address(pc, c)?;
+
+        match operation(x, y) {
+            Some(value) => self.write(address, value),
+            None => return Err(IntcodeError::Overflow { pc: pc }),
+        };
+
+        self.machine.pc = pc + 4;
+
+        return Ok(());
+    }
+
+    // Same contract as Intcode::step.
+    pub fn step(&mut self) -> Result<Option<RunState>, IntcodeError> {
+        let pc = self.machine.pc;
+
+        let op = match self.get_op(pc) {
+            Some(op) => op,
+            None => return self.machine.step(),
+        };
+
+        match op {
+            Op::Add(a, b, c) => self.arithmetic(pc, a, b, c, i64::checked_add)?,
+            Op::Multiply(a, b, c) => self.arithmetic(pc, a, b, c, i64::checked_mul)?,
+            Op::LessThan(a, b, c) => self.arithmetic(pc, a, b, c, |x, y| Some(if x < y {1} else {0}))?,
+            Op::Equals(a, b, c) => self.arithmetic(pc, a, b, c, |x, y| Some(if x == y {1} else {0}))?,
+            Op::Input(a) => {
+                let value = match self.machine.input.pop_front() {
+                    Some(value) => value,
+                    None => return Ok(Some(RunState::NeedsInput)),
+                };
+
+                let address = self.get_address(pc, a)?;
+
+                self.write(address, value);
+                self.machine.pc = pc + 2;
+            },
+            Op::Output(a) => {
+                let value = self.load(pc, a)?;
+
+                self.machine.pc = pc + 2;
+
+                return Ok(Some(RunState::Output(value)));
+            },
+            Op::JumpIfTrue(a, b) | Op::JumpIfFalse(a, b) => {
+                let condition = self.load(pc, a)?;
+                let target = self.load(pc, b)?;
+
+                let jumps = match op {
+                    Op::JumpIfTrue(_, _) => condition != 0,
+                    _ => condition == 0,
+                };
+
+                self.machine.pc = if jumps { CompiledIntcode::jump_target(pc, target)? } else { pc + 3 };
+            },
+            Op::AdjustBase(a) => {
+                let offset = self.load(pc, a)?;
+
+                match self.machine.base.checked_add(offset) {
+                    Some(base) => self.machine.base = base,
+                    None => return Err(IntcodeError::Overflow { pc: pc }),
+                };
+
+                self.machine.pc = pc + 2;
+            },
+            Op::Halt => return Ok(Some(RunState::Halted)),
+        };
+
+        return Ok(None);
+    }
+
+    pub fn run_until_event(&mut self) -> Result<RunState, IntcodeError> {
+        loop {
+            if let Some(state) = self.step()? {
+                return Ok(state);
+            }
+        }
+    }
+
+    pub fn run(&mut self, input: &mut dyn IntcodeInput, output: &mut dyn IntcodeOutput) -> Result<(), IntcodeError> {
+        loop {
+            match self.run_until_event()? {
+                RunState::Halted => break,
+                RunState::NeedsInput => match input.read_input()? {
+                    Some(value) => self.push_input(value),
+                    None => return Err(IntcodeError::InputExhausted { pc: self.machine.pc }),
+                },
+                RunState::Output(out) => output.write_output(out)?,
+            };
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Runs both machines to completion on the same inputs and checks that
+    // they produce the same outputs, final state and result.
+    fn assert_equivalent(program:Vec<i64>, inputs:Vec<i64>) {
+        let mut interpreted = Intcode::new(program.clone());
+        let mut compiled = CompiledIntcode::new(program);
+        let mut interpreted_outputs = vec![];
+        let mut compiled_outputs = vec![];
+
+        let interpreted_result = interpreted.run(&mut inputs.clone(), &mut interpreted_outputs);
+        let compiled_result = compiled.run(&mut inputs.clone(), &mut compiled_outputs);
+
+        assert_eq!(compiled_result, interpreted_result);
+        assert_eq!(compiled_outputs, interpreted_outputs);
+        assert_eq!(*compiled.machine(), interpreted);
+    }
+
+    #[test]
+    fn test_matches_interpreter() {
+        assert_equivalent(vec![1,9,10,3,2,3,11,0,99,30,40,50], vec![]);
+        assert_equivalent(vec![109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99], vec![]);
+        assert_equivalent(vec![1102,34915192,34915192,7,4,7,99,0], vec![]);
+        assert_equivalent(vec![104,1125899906842624,99], vec![]);
+        assert_equivalent(vec![109,10,203,0,204,0,99], vec![42]);
+        assert_equivalent(vec![1101,3,0,100,1001,100,-1,100,1005,100,4,4,100,99], vec![]);
+
+        let comparisons = vec![3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99];
+
+        for input in 6..11 {
+            assert_equivalent(comparisons.clone(), vec![input]);
+        }
+    }
+
+    #[test]
+    fn test_matches_interpreter_errors() {
+        assert_equivalent(vec![1,0,0,0,42], vec![]);
+        assert_equivalent(vec![1,-1,0,0,99], vec![]);
+        assert_equivalent(vec![11101,1,1,5,99], vec![]);
+        assert_equivalent(vec![1105,1,-3,99], vec![]);
+        assert_equivalent(vec![109,-5,204,0,99], vec![]);
+        assert_equivalent(vec![1102,4611686018427387904,2,7,4,7,99,0], vec![]);
+        assert_equivalent(vec![3,0,99], vec![]);
+        assert_equivalent(vec![3,-1,99], vec![4]);
+    }
+
+    #[test]
+    fn test_self_modifying_operand() {
+        // Outputs the immediate at address 1, increments it in place and
+        // loops while it is below 4
+        let program = vec![104,1,1001,1,1,1,1007,1,4,14,1005,14,0,99,0];
+        let mut compiled = CompiledIntcode::new(program.clone());
+        let mut outputs = vec![];
+
+        compiled.run(&mut vec![], &mut outputs).unwrap();
+
+        assert_eq!(outputs, vec![1,2,3]);
+        assert_equivalent(program, vec![]);
+    }
+
+    #[test]
+    fn test_self_modifying_opcode() {
+        // After its first output the program rewrites that instruction into a halt
+        let program = vec![104,1,1101,0,99,0,1105,1,0];
+        let mut compiled = CompiledIntcode::new(program.clone());
+
+        assert_eq!(compiled.run_until_event().unwrap(), RunState::Output(1));
+        assert_eq!(compiled.run_until_event().unwrap(), RunState::Halted);
+        assert_eq!(compiled.machine().pc, 0);
+        assert_equivalent(program, vec![]);
+    }
+
+    #[test]
+    fn test_external_write_invalidates() {
+        let mut compiled = CompiledIntcode::new(vec![3,9,104,7,1105,1,0,99,0,0]);
+
+        compiled.push_input(1);
+
+        assert_eq!(compiled.run_until_event().unwrap(), RunState::Output(7));
+
+        compiled.write(3, 8);
+
+        assert_eq!(compiled.run_until_event().unwrap(), RunState::NeedsInput);
+
+        compiled.push_input(1);
+
+        assert_eq!(compiled.run_until_event().unwrap(), RunState::Output(8));
+    }
+
+    #[test]
+    fn test_code_outside_program() {
+        // Jumps to code written into large_memory, which is never cached
+        let program = vec![1101,0,104,100000,1101,0,5,100001,1101,0,99,100002,1105,1,100000];
+
+        assert_equivalent(program.clone(), vec![]);
+
+        let mut compiled = CompiledIntcode::new(program);
+
+        assert_eq!(compiled.run_until_event().unwrap(), RunState::Output(5));
+    }
+}
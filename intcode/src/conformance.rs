@@ -0,0 +1,313 @@
+use bigint::BigInt;
+use compile::CompiledIntcode;
+use error::IntcodeError;
+use machine::{Intcode, RunState};
+
+// The operations the conformance suite needs from an Intcode implementation.
+pub trait IntcodeVm {
+    fn load(program:Vec<i64>) -> Self;
+    fn push_input(&mut self, value:i64);
+    fn step(&mut self) -> Result<Option<RunState>, IntcodeError>;
+    fn read(&self, address:usize) -> Option<i64>;
+
+    fn run_until_event(&mut self) -> Result<RunState, IntcodeError> {
+        loop {
+            if let Some(state) = self.step()? {
+                return Ok(state);
+            }
+        }
+    }
+}
+
+impl IntcodeVm for Intcode {
+    fn load(program:Vec<i64>) -> Intcode {
+        Intcode::new(program)
+    }
+
+    fn push_input(&mut self, value:i64) {
+        Intcode::push_input(self, value);
+    }
+
+    fn step(&mut self) -> Result<Option<RunState>, IntcodeError> {
+        Intcode::step(self)
+    }
+
+    fn read(&self, address:usize) -> Option<i64> {
+        Some(Intcode::read(self, address))
+    }
+}
+
+impl IntcodeVm for CompiledIntcode {
+    fn load(program:Vec<i64>) -> CompiledIntcode {
+        CompiledIntcode::new(program)
+    }
+
+    fn push_input(&mut self, value:i64) {
+        CompiledIntcode::push_input(self, value);
+    }
+
+    fn step(&mut self) -> Result<Option<RunState>, IntcodeError> {
+        CompiledIntcode::step(self)
+    }
+
+    fn read(&self, address:usize) -> Option<i64> {
+        Some(CompiledIntcode::read(self, address))
+    }
+}
+
+impl IntcodeVm for Intcode<BigInt> {
+    fn load(program:Vec<i64>) -> Intcode<BigInt> {
+        Intcode::from_words(program.into_iter().map(BigInt::from_i64).collect())
+    }
+
+    fn push_input(&mut self, value:i64) {
+        Intcode::push_input(self, BigInt::from_i64(value));
+    }
+
+    fn step(&mut self) -> Result<Option<RunState>, IntcodeError> {
+        match Intcode::step(self)? {
+            None => Ok(None),
+            Some(RunState::Halted) => Ok(Some(RunState::Halted)),
+            Some(RunState::NeedsInput) => Ok(Some(RunState::NeedsInput)),
+            Some(RunState::Output(out)) => match out.to_i64() {
+                Some(value) => Ok(Some(RunState::Output(value))),
+                None => Err(IntcodeError::Overflow { pc: self.pc }),
+            },
+        }
+    }
+
+    fn read(&self, address:usize) -> Option<i64> {
+        Intcode::read(self, address).to_i64()
+    }
+}
+
+#[derive(PartialEq,Debug)]
+pub enum ConformanceCase {
+    // Runs with all inputs queued up front. An empty memory means the final
+    // memory isn't checked; otherwise it must match from address 0.
+    Program { name: &'static str, program: Vec<i64>, inputs: Vec<i64>, outputs: Vec<i64>, memory: Vec<i64> },
+    // One machine per phase, each feeding its outputs to the next and the
+    // last feeding the first, starting from a signal of 0.
+    Amplifiers { name: &'static str, program: Vec<i64>, phases: Vec<i64>, output: i64 },
+}
+
+fn program(name:&'static str, program:Vec<i64>, inputs:Vec<i64>, outputs:Vec<i64>) -> ConformanceCase {
+    ConformanceCase::Program { name: name, program: program, inputs: inputs, outputs: outputs, memory: vec![] }
+}
+
+fn final_memory(name:&'static str, program:Vec<i64>, memory:Vec<i64>) -> ConformanceCase {
+    ConformanceCase::Program { name: name, program: program, inputs: vec![], outputs: vec![], memory: memory }
+}
+
+fn amplifiers(name:&'static str, program:Vec<i64>, phases:Vec<i64>, output:i64) -> ConformanceCase {
+    ConformanceCase::Amplifiers { name: name, program: program, phases: phases, output: output }
+}
+
+// Every example program published with the 2019 Intcode puzzles.
+pub fn conformance_cases() -> Vec<ConformanceCase> {
+    let equal_to_8_position = vec![3,9,8,9,10,9,4,9,99,-1,8];
+    let less_than_8_position = vec![3,9,7,9,10,9,4,9,99,-1,8];
+    let equal_to_8_immediate = vec![3,3,1108,-1,8,3,4,3,99];
+    let less_than_8_immediate = vec![3,3,1107,-1,8,3,4,3,99];
+    let jump_position = vec![3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9];
+    let jump_immediate = vec![3,3,1105,-1,9,1101,0,0,12,4,12,99,1];
+    let compare_to_8 = vec![3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99];
+    let quine = vec![109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99];
+
+    return vec![
+        final_memory("day 2 example", vec![1,9,10,3,2,3,11,0,99,30,40,50], vec![3500,9,10,70,2,3,11,0,99,30,40,50]),
+        final_memory("day 2 add", vec![1,0,0,0,99], vec![2,0,0,0,99]),
+        final_memory("day 2 multiply", vec![2,3,0,3,99], vec![2,3,0,6,99]),
+        final_memory("day 2 multiply past halt", vec![2,4,4,5,99,0], vec![2,4,4,5,99,9801]),
+        final_memory("day 2 self-modifying", vec![1,1,1,4,99,5,6,0,99], vec![30,1,1,4,2,5,6,0,99]),
+        program("day 5 echo", vec![3,0,4,0,99], vec![42], vec![42]),
+        final_memory("day 5 immediate mode", vec![1002,4,3,4,33], vec![1002,4,3,4,99]),
+        final_memory("day 5 negative immediate", vec![1101,100,-1,4,0], vec![1101,100,-1,4,99]),
+        program("day 5 equal to 8, position mode, equal", equal_to_8_position.clone(), vec![8], vec![1]),
+        program("day 5 equal to 8, position mode, not equal", equal_to_8_position, vec![7], vec![0]),
+        program("day 5 less than 8, position mode, less", less_than_8_position.clone(), vec![7], vec![1]),
+        program("day 5 less than 8, position mode, not less", less_than_8_position, vec![8], vec![0]),
+        program("day 5 equal to 8, immediate mode, equal", equal_to_8_immediate.clone(), vec![8], vec![1]),
+        program("day 5 equal to 8, immediate mode, not equal", equal_to_8_immediate, vec![9], vec![0]),
+        program("day 5 less than 8, immediate mode, less", less_than_8_immediate.clone(), vec![7], vec![1]),
+        program("day 5 less than 8, immediate mode, not less", less_than_8_immediate, vec![10], vec![0]),
+        program("day 5 jump, position mode, zero", jump_position.clone(), vec![0], vec![0]),
+        program("day 5 jump, position mode, non-zero", jump_position, vec![5], vec![1]),
+        program("day 5 jump, immediate mode, zero", jump_immediate.clone(), vec![0], vec![0]),
+        program("day 5 jump, immediate mode, non-zero", jump_immediate, vec![5], vec![1]),
+        program("day 5 compare to 8, below", compare_to_8.clone(), vec![7], vec![999]),
+        program("day 5 compare to 8, equal", compare_to_8.clone(), vec![8], vec![1000]),
+        program("day 5 compare to 8, above", compare_to_8, vec![9], vec![1001]),
+        amplifiers("day 7 chain 43210", vec![3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0], vec![4,3,2,1,0], 43210),
+        amplifiers("day 7 chain 54321", vec![3,23,3,24,1002,24,10,24,1002,23,-1,23,101,5,23,23,1,24,23,23,4,23,99,0,0], vec![0,1,2,3,4], 54321),
+        amplifiers("day 7 chain 65210", vec![3,31,3,32,1002,32,10,32,1001,31,-2,31,1007,31,0,33,1002,33,7,33,1,33,31,31,1,32,31,31,4,31,99,0,0,0], vec![1,0,4,3,2], 65210),
+        amplifiers("day 7 feedback 139629729", vec![3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5], vec![9,8,7,6,5], 139629729),
+        amplifiers("day 7 feedback 18216", vec![3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,-5,54,1105,1,12,1,53,54,53,1008,54,0,55,1001,55,1,55,2,53,55,53,4,53,1001,56,-1,56,1005,56,6,99,0,0,0,0,10], vec![9,7,8,5,6], 18216),
+        program("day 9 quine", quine.clone(), vec![], quine),
+        program("day 9 16-digit product", vec![1102,34915192,34915192,7,4,7,99,0], vec![], vec![1219070632396864]),
+        program("day 9 large number", vec![104,1125899906842624,99], vec![], vec![1125899906842624]),
+    ];
+}
+
+fn run_program<V: IntcodeVm>(program:&Vec<i64>, inputs:&Vec<i64>) -> Result<(V, Vec<i64>), String> {
+    let mut vm = V::load(program.clone());
+    let mut outputs = vec![];
+
+    for &input in inputs {
+        vm.push_input(input);
+    }
+
+    loop {
+        match vm.run_until_event() {
+            Ok(RunState::Halted) => return Ok((vm, outputs)),
+            Ok(RunState::Output(out)) => outputs.push(out),
+            Ok(RunState::NeedsInput) => return Err("ran out of input".to_string()),
+            Err(error) => return Err(error.to_string()),
+        };
+    }
+}
+
+fn run_amplifiers<V: IntcodeVm>(program:&Vec<i64>, phases:&Vec<i64>) -> Result<i64, String> {
+    let mut amplifiers: Vec<V> = phases.iter().map(|&phase| {
+        let mut vm = V::load(program.clone());
+        vm.push_input(phase);
+        vm
+    }).collect();
+
+    let last = amplifiers.len() - 1;
+    let mut signal = 0;
+    let mut output = None;
+    let mut i = 0;
+
+    loop {
+        amplifiers[i].push_input(signal);
+
+        match amplifiers[i].run_until_event() {
+            Ok(RunState::Output(out)) => {
+                signal = out;
+
+                if i == last {
+                    output = Some(out);
+                }
+            },
+            Ok(RunState::Halted) if i == last => break,
+            Ok(RunState::Halted) => (),
+            Ok(RunState::NeedsInput) => return Err(format!("amplifier {} ran out of input", i)),
+            Err(error) => return Err(format!("amplifier {}: {}", i, error)),
+        };
+
+        i = (i + 1) % amplifiers.len();
+    }
+
+    return output.ok_or("no output from the last amplifier".to_string());
+}
+
+pub fn run_conformance_case<V: IntcodeVm>(case:&ConformanceCase) -> Result<(), String> {
+    match *case {
+        ConformanceCase::Program { ref program, ref inputs, ref outputs, ref memory, .. } => {
+            let (vm, actual) = run_program::<V>(program, inputs)?;
+
+            if actual != *outputs {
+                return Err(format!("expected outputs {:?}, got {:?}", outputs, actual));
+            }
+
+            for (address, &expected) in memory.iter().enumerate() {
+                if vm.read(address) != Some(expected) {
+                    return Err(format!("expected {} at address {}, got {:?}", expected, address, vm.read(address)));
+                }
+            }
+        },
+        ConformanceCase::Amplifiers { ref program, ref phases, output, .. } => {
+            let actual = run_amplifiers::<V>(program, phases)?;
+
+            if actual != output {
+                return Err(format!("expected output {}, got {}", output, actual));
+            }
+        },
+    };
+
+    return Ok(());
+}
+
+// Runs every case against the VM and describes each one that failed.
+pub fn run_conformance_suite<V: IntcodeVm>() -> Vec<String> {
+    let mut failures = vec![];
+
+    for case in conformance_cases() {
+        let name = match case {
+            ConformanceCase::Program { name, .. } | ConformanceCase::Amplifiers { name, .. } => name,
+        };
+
+        if let Err(message) = run_conformance_case::<V>(&case) {
+            failures.push(format!("{}: {}", name, message));
+        }
+    }
+
+    return failures;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Outputs one more than the real machine, to check failures are reported
+    struct OffByOne(Intcode);
+
+    impl IntcodeVm for OffByOne {
+        fn load(program:Vec<i64>) -> OffByOne {
+            OffByOne(Intcode::new(program))
+        }
+
+        fn push_input(&mut self, value:i64) {
+            self.0.push_input(value);
+        }
+
+        fn step(&mut self) -> Result<Option<RunState>, IntcodeError> {
+            match self.0.step()? {
+                Some(RunState::Output(out)) => Ok(Some(RunState::Output(out + 1))),
+                state => Ok(state),
+            }
+        }
+
+        fn read(&self, address:usize) -> Option<i64> {
+            Some(self.0.read(address))
+        }
+    }
+
+    #[test]
+    fn test_interpreter() {
+        assert_eq!(run_conformance_suite::<Intcode>(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_compiled() {
+        assert_eq!(run_conformance_suite::<CompiledIntcode>(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_big_integer() {
+        assert_eq!(run_conformance_suite::<Intcode<BigInt>>(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_failures_are_reported() {
+        let failures = run_conformance_suite::<OffByOne>();
+
+        assert!(failures.contains(&"day 5 echo: expected outputs [42], got [43]".to_string()));
+
+        // Only the cases that check memory rather than outputs still pass
+        let passed: Vec<&str> = conformance_cases().into_iter().map(|case| match case {
+            ConformanceCase::Program { name, .. } | ConformanceCase::Amplifiers { name, .. } => name,
+        }).filter(|name| !failures.iter().any(|failure| failure.starts_with(&format!("{}: ", name)))).collect();
+
+        assert_eq!(passed, vec![
+            "day 2 example",
+            "day 2 add",
+            "day 2 multiply",
+            "day 2 multiply past halt",
+            "day 2 self-modifying",
+            "day 5 immediate mode",
+            "day 5 negative immediate",
+        ]);
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::{BTreeSet, HashMap};
+
+use disassemble::format_instruction;
+use error::IntcodeError;
+use instruction::get_instruction_at_index;
+use machine::{Intcode, RunState};
+
+// Larger dumps are refused rather than printing for ages
+const MAX_DUMP_WORDS: usize = 4096;
+
+// How long continue runs before giving control back, unless changed
+pub const DEFAULT_MAX_STEPS: u64 = 10000000;
+
+const HELP: &str = "\
+step [n]          execute n instructions (default 1)
+continue          run until a breakpoint, watchpoint, input request, halt or the step limit
+break <pc>        set a breakpoint
+delete <pc>       remove a breakpoint
+watch <addr>      stop when the value at addr changes
+unwatch <addr>    remove a watchpoint
+input <v>...      queue input values
+regs              show pc, relative base, queued input and next instruction
+mem <addr> [n]    dump n words of memory starting at addr (default 16)
+large             dump the large_memory overflow region
+outputs           show every value output so far
+quit              exit the debugger";
+
+#[derive(PartialEq,Debug)]
+pub enum StopReason {
+    Stepped,
+    Breakpoint(usize),
+    Watchpoint { address: usize, old: i64, new: i64 },
+    NeedsInput,
+    Halted,
+    StepLimit(u64),
+    Error(IntcodeError),
+}
+
+pub struct Debugger {
+    pub machine: Intcode,
+    pub breakpoints: BTreeSet<usize>,
+    pub watchpoints: BTreeSet<usize>,
+    pub outputs: Vec<i64>,
+    pub max_steps: u64,
+}
+
+impl Debugger {
+    pub fn new(program:Vec<i64>) -> Debugger {
+        Debugger {
+            machine: Intcode::new(program),
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+            outputs: vec![],
+            max_steps: DEFAULT_MAX_STEPS,
+        }
+    }
+
+    pub fn step(&mut self) -> StopReason {
+        let watched: Vec<(usize, i64)> = self.watchpoints.iter().map(|&address| (address, self.machine.read(address))).collect();
+
+        match self.machine.step() {
+            Err(error) => return StopReason::Error(error),
+            Ok(Some(RunState::Halted)) => return StopReason::Halted,
+            Ok(Some(RunState::NeedsInput)) => return StopReason::NeedsInput,
+            Ok(Some(RunState::Output(out))) => self.outputs.push(out),
+            Ok(None) => (),
+        };
+
+        for (address, old) in watched {
+            let new = self.machine.read(address);
+
+            if new != old {
+                return StopReason::Watchpoint { address: address, old: old, new: new };
+            }
+        }
+
+        return StopReason::Stepped;
+    }
+
+    // Always executes at least one instruction, so continuing from a
+    // breakpoint moves past it. Stops after max_steps so that a program
+    // stuck in a loop doesn't hang the debugger.
+    pub fn continue_execution(&mut self) -> StopReason {
+        for _ in 0..self.max_steps.max(1) {
+            let reason = self.step();
+
+            if reason != StopReason::Stepped {
+                return reason;
+            }
+
+            if self.breakpoints.contains(&self.machine.pc) {
+                return StopReason::Breakpoint(self.machine.pc);
+            }
+        }
+
+        return StopReason::StepLimit(self.max_steps.max(1));
+    }
+
+    pub fn describe_current_instruction(&self) -> String {
+        match get_instruction_at_index(self.machine.pc, &self.machine.memory) {
+            Ok(instruction) => format!("{:04}  {}", self.machine.pc, format_instruction(&instruction, &HashMap::new())),
+            Err(error) => format!("{:04}  <{}>", self.machine.pc, error),
+        }
+    }
+
+    fn describe_stop(&self, reason:&StopReason, new_outputs:usize) -> String {
+        let mut lines = vec![];
+
+        for out in &self.outputs[self.outputs.len() - new_outputs..] {
+            lines.push(format!("output: {}", out));
+        }
+
+        match *reason {
+            StopReason::Stepped => (),
+            StopReason::Breakpoint(pc) => lines.push(format!("breakpoint at {}", pc)),
+            StopReason::Watchpoint { address, old, new } => lines.push(format!("watchpoint [{}]: {} -> {}", address, old, new)),
+            StopReason::NeedsInput => lines.push("waiting for input".to_string()),
+            StopReason::Halted => lines.push("halted".to_string()),
+            StopReason::StepLimit(steps) => lines.push(format!("step limit reached after {} steps", steps)),
+            StopReason::Error(ref error) => lines.push(format!("error: {}", error)),
+        };
+
+        lines.push(self.describe_current_instruction());
+
+        return lines.join("\n");
+    }
+
+    fn dump_memory(&self, start:usize, count:usize) -> String {
+        if count > MAX_DUMP_WORDS {
+            return format!("error: can't dump more than {} words at once", MAX_DUMP_WORDS);
+        }
+
+        let end = match start.checked_add(count) {
+            Some(end) => end,
+            None => return format!("error: {} words from {} is past the end of memory", count, start),
+        };
+
+        let mut lines = vec![];
+
+        for row_start in (start..end).step_by(8) {
+            let row_end = row_start.saturating_add(8).min(end);
+            let words: Vec<String> = (row_start..row_end).map(|address| format!("{:>6}", self.machine.read(address))).collect();
+
+            lines.push(format!("{:04}: {}", row_start, words.join(" ")));
+        }
+
+        return lines.join("\n");
+    }
+
+    fn dump_large_memory(&self) -> String {
+        let mut entries: Vec<(&usize, &i64)> = self.machine.memory.large_memory.iter().collect();
+
+        if entries.is_empty() {
+            return "large_memory is empty".to_string();
+        }
+
+        entries.sort();
+
+        let lines: Vec<String> = entries.iter().map(|&(address, value)| format!("{}: {}", address, value)).collect();
+
+        return lines.join("\n");
+    }
+
+    // Runs one REPL command and returns the text to show. None means quit.
+    pub fn execute_command(&mut self, command:&str) -> Option<String> {
+        let tokens: Vec<&str> = command.split_whitespace().collect();
+
+        if tokens.is_empty() {
+            return Some(String::new());
+        }
+
+        let mut numbers = vec![];
+
+        for token in &tokens[1..] {
+            match token.parse::<i64>() {
+                Ok(n) => numbers.push(n),
+                Err(_) => return Some(format!("not a number: {}", token)),
+            };
+        }
+
+        let address = |i:usize| -> Option<usize> {
+            match numbers.get(i) {
+                Some(&n) if n >= 0 => Some(n as usize),
+                _ => None,
+            }
+        };
+
+        let response = match tokens[0] {
+            "s" | "step" => {
+                let count = match numbers.first() {
+                    Some(_) => match address(0) {
+                        Some(count) => count,
+                        None => return Some(format!("invalid count: {}", numbers[0])),
+                    },
+                    None => 1,
+                };
+                let outputs_before = self.outputs.len();
+                let mut reason = StopReason::Stepped;
+
+                for _ in 0..count {
+                    reason = self.step();
+
+                    if reason != StopReason::Stepped {
+                        break;
+                    }
+                }
+
+                self.describe_stop(&reason, self.outputs.len() - outputs_before)
+            },
+            "c" | "continue" => {
+                let outputs_before = self.outputs.len();
+                let reason = self.continue_execution();
+
+                self.describe_stop(&reason, self.outputs.len() - outputs_before)
+            },
+            "b" | "break" => match address(0) {
+                Some(pc) => {
+                    self.breakpoints.insert(pc);
+                    format!("breakpoint set at {}", pc)
+                },
+                None => format!("breakpoints: {:?}", self.breakpoints),
+            },
+            "d" | "delete" => match address(0) {
+                Some(pc) if self.breakpoints.remove(&pc) => format!("breakpoint at {} removed", pc),
+                _ => "no such breakpoint".to_string(),
+            },
+            "w" | "watch" => match address(0) {
+                Some(a) => {
+                    self.watchpoints.insert(a);
+                    format!("watching [{}] = {}", a, self.machine.read(a))
+                },
+                None => format!("watchpoints: {:?}", self.watchpoints),
+            },
+            "unwatch" => match address(0) {
+                Some(a) if self.watchpoints.remove(&a) => format!("watchpoint on [{}] removed", a),
+                _ => "no such watchpoint".to_string(),
+            },
+            "i" | "input" => {
+                for &n in &numbers {
+                    self.machine.push_input(n);
+                }
+
+                format!("input queue: {:?}", self.machine.input)
+            },
+            "r" | "regs" => format!("pc: {}\nrelative base: {}\ninput queue: {:?}\n{}", self.machine.pc, self.machine.base, self.machine.input, self.describe_current_instruction()),
+            "x" | "mem" => match (address(0), numbers.get(1)) {
+                (None, _) => "usage: mem <addr> [n]".to_string(),
+                (Some(start), None) => self.dump_memory(start, 16),
+                (Some(start), Some(&count)) => match address(1) {
+                    Some(count) => self.dump_memory(start, count),
+                    None => format!("invalid count: {}", count),
+                },
+            },
+            "large" => self.dump_large_memory(),
+            "outputs" => format!("{:?}", self.outputs),
+            "h" | "help" => HELP.to_string(),
+            "q" | "quit" => return None,
+            _ => format!("unknown command: {} (try help)", tokens[0]),
+        };
+
+        return Some(response);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Day 5 example: outputs 999, 1000 or 1001 for input below, equal to or above 8
+    fn day_5_program() -> Vec<i64> {
+        vec![3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99]
+    }
+
+    #[test]
+    fn test_step_and_continue() {
+        let mut debugger = Debugger::new(day_5_program());
+
+        assert_eq!(debugger.continue_execution(), StopReason::NeedsInput);
+
+        debugger.machine.push_input(8);
+
+        assert_eq!(debugger.step(), StopReason::Stepped);
+        assert_eq!(debugger.machine.pc, 2);
+        assert_eq!(debugger.continue_execution(), StopReason::Halted);
+        assert_eq!(debugger.outputs, vec![1000]);
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut debugger = Debugger::new(day_5_program());
+
+        debugger.breakpoints.insert(22);
+        debugger.machine.push_input(8);
+
+        assert_eq!(debugger.continue_execution(), StopReason::Breakpoint(22));
+        assert_eq!(debugger.continue_execution(), StopReason::Halted);
+    }
+
+    #[test]
+    fn test_watchpoints() {
+        let mut debugger = Debugger::new(day_5_program());
+
+        debugger.watchpoints.insert(21);
+        debugger.machine.push_input(9);
+
+        assert_eq!(debugger.continue_execution(), StopReason::Watchpoint { address: 21, old: 0, new: 9 });
+        assert_eq!(debugger.machine.pc, 2);
+    }
+
+    #[test]
+    fn test_error_stops_execution() {
+        let mut debugger = Debugger::new(vec![1,0,0,0,42]);
+
+        assert_eq!(debugger.continue_execution(), StopReason::Error(IntcodeError::InvalidOpcode { pc: 4, opcode: 42 }));
+    }
+
+    #[test]
+    fn test_execute_command() {
+        let mut debugger = Debugger::new(day_5_program());
+
+        assert_eq!(debugger.execute_command("regs").unwrap(), "pc: 0\nrelative base: 0\ninput queue: []\n0000  IN -> [21]");
+        assert_eq!(debugger.execute_command("break 31").unwrap(), "breakpoint set at 31");
+        assert_eq!(debugger.execute_command("input 7").unwrap(), "input queue: [7]");
+        assert_eq!(debugger.execute_command("continue").unwrap(), "breakpoint at 31\n0031  OUT #999");
+        assert_eq!(debugger.execute_command("step").unwrap(), "output: 999\n0033  JT #1, #46");
+        assert_eq!(debugger.execute_command("mem 19 3").unwrap(), "0019:     98      0      7");
+        assert_eq!(debugger.execute_command("c").unwrap(), "halted\n0046  HLT");
+        assert_eq!(debugger.execute_command("bogus").unwrap(), "unknown command: bogus (try help)");
+        assert_eq!(debugger.execute_command("quit"), None);
+    }
+
+    #[test]
+    fn test_step_limit() {
+        // Jumps back to itself forever
+        let mut debugger = Debugger::new(vec![1105,1,0]);
+
+        debugger.max_steps = 100;
+
+        assert_eq!(debugger.continue_execution(), StopReason::StepLimit(100));
+        assert_eq!(debugger.execute_command("c").unwrap(), "step limit reached after 100 steps\n0000  JT #1, #0");
+    }
+
+    #[test]
+    fn test_invalid_counts() {
+        let mut debugger = Debugger::new(day_5_program());
+
+        assert_eq!(debugger.execute_command("mem 0 -5").unwrap(), "invalid count: -5");
+        assert_eq!(debugger.execute_command("step -1").unwrap(), "invalid count: -1");
+        assert_eq!(debugger.machine.pc, 0);
+    }
+
+    #[test]
+    fn test_dump_large_memory() {
+        let mut debugger = Debugger::new(vec![99]);
+
+        assert_eq!(debugger.execute_command("large").unwrap(), "large_memory is empty");
+
+        debugger.machine.write(1000000000, 5);
+
+        assert_eq!(debugger.execute_command("large").unwrap(), "1000000000: 5");
+        assert_eq!(debugger.execute_command("mem 1000000000 1").unwrap(), "1000000000:      5");
+    }
+
+    #[test]
+    fn test_dump_memory_out_of_range() {
+        let mut debugger = Debugger::new(vec![99]);
+
+        assert_eq!(debugger.dump_memory(usize::MAX, 16), "error: 16 words from 18446744073709551615 is past the end of memory");
+        assert_eq!(debugger.dump_memory(usize::MAX - 10, 10), "18446744073709551605:      0      0      0      0      0      0      0      0\n18446744073709551613:      0      0");
+        assert_eq!(debugger.execute_command("mem 0 9223372036854775807").unwrap(), "error: can't dump more than 4096 words at once");
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+
+use instruction::{encode_instruction_code, get_instruction_at_index, get_parameters, get_write_parameter, Instruction, ParameterMode};
+use memory::Memory;
+
+#[derive(PartialEq,Debug)]
+pub enum Line {
+    Code(usize, Instruction),
+    Data(usize, Vec<i64>),
+}
+
+pub fn get_mnemonic(code:i64) -> Option<&'static str> {
+    match code {
+        1 => Some("ADD"),
+        2 => Some("MUL"),
+        3 => Some("IN"),
+        4 => Some("OUT"),
+        5 => Some("JT"),
+        6 => Some("JF"),
+        7 => Some("LT"),
+        8 => Some("EQ"),
+        9 => Some("ARB"),
+        99 => Some("HLT"),
+        _ => None,
+    }
+}
+
+fn decode_at(idx:usize, memory:&Memory, program_length:usize) -> Option<Instruction> {
+    let instruction = match get_instruction_at_index(idx, memory) {
+        Ok(instruction) => instruction,
+        Err(_) => return None,
+    };
+
+    if idx + instruction.size > program_length || encode_instruction_code(&instruction) != memory.read(idx) {
+        return None;
+    }
+
+    if let Some(n) = get_write_parameter(instruction.code) {
+        if *get_parameters(&instruction)[n - 1].0 == ParameterMode::Immediate {
+            return None;
+        }
+    }
+
+    return Some(instruction);
+}
+
+// Linear sweep from address 0. Words that don't decode to a well-formed
+// instruction (or whose encoding wouldn't survive re-assembly) become data.
+pub fn decode_program(program:&Vec<i64>) -> Vec<Line> {
+    let memory = Memory::new(program.clone());
+    let mut lines = vec![];
+    let mut idx = 0;
+
+    while idx < program.len() {
+        match decode_at(idx, &memory, program.len()) {
+            Some(instruction) => {
+                let size = instruction.size;
+
+                lines.push(Line::Code(idx, instruction));
+                idx = idx + size;
+            },
+            None => {
+                let extend = match lines.last_mut() {
+                    Some(&mut Line::Data(_, ref mut words)) => {
+                        words.push(program[idx]);
+                        true
+                    },
+                    _ => false,
+                };
+
+                if !extend {
+                    lines.push(Line::Data(idx, vec![program[idx]]));
+                }
+
+                idx = idx + 1;
+            },
+        };
+    }
+
+    return lines;
+}
+
+pub fn get_jump_labels(lines:&Vec<Line>) -> HashMap<usize,String> {
+    let mut code_addresses = vec![];
+    let mut targets = vec![];
+
+    for line in lines {
+        if let Line::Code(address, ref instruction) = *line {
+            code_addresses.push(address);
+
+            if [5,6].contains(&instruction.code) && instruction.p2_mode == ParameterMode::Immediate && instruction.p2 >= 0 {
+                targets.push(instruction.p2 as usize);
+            }
+        }
+    }
+
+    targets.sort();
+    targets.dedup();
+
+    return targets.into_iter()
+        .filter(|target| code_addresses.contains(target))
+        .enumerate()
+        .map(|(i, target)| (target, format!("L{}", i)))
+        .collect();
+}
+
+pub fn format_parameter(mode:&ParameterMode, value:i64) -> String {
+    match *mode {
+        ParameterMode::Immediate => format!("#{}", value),
+        ParameterMode::Position => format!("[{}]", value),
+        ParameterMode::Relative => if value < 0 { format!("[rb{}]", value) } else { format!("[rb+{}]", value) },
+    }
+}
+
+pub fn format_instruction(instruction:&Instruction, labels:&HashMap<usize,String>) -> String {
+    let mnemonic = get_mnemonic(instruction.code).unwrap_or("???");
+    let write_parameter = get_write_parameter(instruction.code);
+
+    let mut reads = vec![];
+    let mut write = None;
+
+    for (i, (mode, value)) in get_parameters(instruction).into_iter().enumerate() {
+        let mut operand = format_parameter(mode, value);
+
+        if [5,6].contains(&instruction.code) && i == 1 && *mode == ParameterMode::Immediate && value >= 0 {
+            if let Some(label) = labels.get(&(value as usize)) {
+                operand = format!("#{}", label);
+            }
+        }
+
+        if write_parameter == Some(i + 1) {
+            write = Some(operand);
+        } else {
+            reads.push(operand);
+        }
+    }
+
+    let mut text = mnemonic.to_string();
+
+    if !reads.is_empty() {
+        text = format!("{} {}", text, reads.join(", "));
+    }
+
+    if let Some(operand) = write {
+        text = format!("{} -> {}", text, operand);
+    }
+
+    return text;
+}
+
+pub fn disassemble(program:&Vec<i64>) -> String {
+    let lines = decode_program(program);
+    let labels = get_jump_labels(&lines);
+
+    let mut output = String::new();
+
+    for line in &lines {
+        match *line {
+            Line::Code(address, ref instruction) => {
+                if let Some(label) = labels.get(&address) {
+                    output.push_str(&format!("{}:\n", label));
+                }
+
+                output.push_str(&format!("{:04}  {}\n", address, format_instruction(instruction, &labels)));
+            },
+            Line::Data(address, ref words) => {
+                let words: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+
+                output.push_str(&format!("{:04}  .data {}\n", address, words.join(", ")));
+            },
+        };
+    }
+
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_parameter() {
+        assert_eq!(format_parameter(&ParameterMode::Position, 12), "[12]");
+        assert_eq!(format_parameter(&ParameterMode::Immediate, 5), "#5");
+        assert_eq!(format_parameter(&ParameterMode::Immediate, -5), "#-5");
+        assert_eq!(format_parameter(&ParameterMode::Relative, 3), "[rb+3]");
+        assert_eq!(format_parameter(&ParameterMode::Relative, -1), "[rb-1]");
+    }
+
+    #[test]
+    fn test_format_instruction() {
+        let instruction = get_instruction_at_index(0, &Memory::new(vec![21001,12,5,3])).unwrap();
+
+        assert_eq!(format_instruction(&instruction, &HashMap::new()), "ADD [12], #5 -> [rb+3]");
+
+        let instruction = get_instruction_at_index(0, &Memory::new(vec![203,-4])).unwrap();
+
+        assert_eq!(format_instruction(&instruction, &HashMap::new()), "IN -> [rb-4]");
+
+        let instruction = get_instruction_at_index(0, &Memory::new(vec![99])).unwrap();
+
+        assert_eq!(format_instruction(&instruction, &HashMap::new()), "HLT");
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let text = disassemble(&vec![3,9,8,9,10,9,4,9,99,-1,8]);
+
+        assert_eq!(text, "0000  IN -> [9]\n\
+                          0002  EQ [9], [10] -> [9]\n\
+                          0006  OUT [9]\n\
+                          0008  HLT\n\
+                          0009  .data -1, 8\n");
+    }
+
+    #[test]
+    fn test_disassemble_jump_labels() {
+        let text = disassemble(&vec![3,3,1105,-1,9,1101,0,0,12,4,12,99,1]);
+
+        assert_eq!(text, "0000  IN -> [3]\n\
+                          0002  JT #-1, #L0\n\
+                          0005  ADD #0, #0 -> [12]\n\
+                          L0:\n\
+                          0009  OUT [12]\n\
+                          0011  HLT\n\
+                          0012  .data 1\n");
+    }
+
+    #[test]
+    fn test_decode_program_data() {
+        // Truncated instruction, immediate-mode write and non-canonical encoding are all data
+        assert_eq!(decode_program(&vec![99,1,2]), vec![Line::Code(0, get_instruction_at_index(0, &Memory::new(vec![99])).unwrap()), Line::Data(1, vec![1,2])]);
+        assert_eq!(decode_program(&vec![11101,1,1,0]), vec![Line::Data(0, vec![11101,1,1,0])]);
+        assert_eq!(decode_program(&vec![10099]), vec![Line::Data(0, vec![10099])]);
+    }
+}
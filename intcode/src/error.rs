@@ -0,0 +1,75 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use budget::BudgetLimit;
+
+#[derive(PartialEq,Debug)]
+pub enum IntcodeError {
+    InvalidOpcode { pc: usize, opcode: i64 },
+    InvalidParameterMode { instruction: i64 },
+    NegativeAddress { pc: usize, address: i64 },
+    ImmediateModeWrite { pc: usize },
+    InputExhausted { pc: usize },
+    ParseError { token: String },
+    AssembleError { line: usize, message: String },
+    IoError { message: String },
+    InvalidSnapshot { message: String },
+    NetworkNodeError { node: usize, error: Box<IntcodeError> },
+    Overflow { pc: usize },
+    BudgetExceeded { pc: usize, limit: BudgetLimit, instructions: u64, memory: usize },
+    InvalidPatch { address: usize, message: String },
+}
+
+impl fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IntcodeError::InvalidOpcode { pc, opcode } => write!(f, "invalid opcode {} at pc {}", opcode, pc),
+            IntcodeError::InvalidParameterMode { instruction } => write!(f, "invalid parameter mode in instruction {}", instruction),
+            IntcodeError::NegativeAddress { pc, address } => write!(f, "negative address {} used at pc {}", address, pc),
+            IntcodeError::ImmediateModeWrite { pc } => write!(f, "write parameter in immediate mode at pc {}", pc),
+            IntcodeError::InputExhausted { pc } => write!(f, "input exhausted at pc {}", pc),
+            IntcodeError::ParseError { ref token } => write!(f, "could not parse {:?} as an integer", token),
+            IntcodeError::AssembleError { line, ref message } => write!(f, "line {}: {}", line, message),
+            IntcodeError::IoError { ref message } => write!(f, "i/o error: {}", message),
+            IntcodeError::InvalidSnapshot { ref message } => write!(f, "invalid snapshot: {}", message),
+            IntcodeError::NetworkNodeError { node, ref error } => write!(f, "node {}: {}", node, error),
+            IntcodeError::Overflow { pc } => write!(f, "integer overflow at pc {}", pc),
+            IntcodeError::BudgetExceeded { pc, limit, instructions, .. } => write!(f, "budget of {} exceeded at pc {} after {} instructions", limit, pc, instructions),
+            IntcodeError::InvalidPatch { address, ref message } => write!(f, "patch at address {}: {}", address, message),
+        }
+    }
+}
+
+impl Error for IntcodeError {}
+
+impl From<io::Error> for IntcodeError {
+    fn from(error: io::Error) -> IntcodeError {
+        IntcodeError::IoError { message: error.to_string() }
+    }
+}
+
+impl From<IntcodeError> for io::Error {
+    fn from(error: IntcodeError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(IntcodeError::InvalidOpcode { pc: 4, opcode: 42 }.to_string(), "invalid opcode 42 at pc 4");
+        assert_eq!(IntcodeError::ParseError { token: "x".to_string() }.to_string(), "could not parse \"x\" as an integer");
+    }
+
+    #[test]
+    fn test_into_io_error() {
+        let error: io::Error = IntcodeError::InputExhausted { pc: 2 }.into();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "input exhausted at pc 2");
+    }
+}
@@ -0,0 +1,483 @@
+use std::cmp;
+
+use conformance::IntcodeVm;
+use error::IntcodeError;
+use machine::RunState;
+
+// Generated programs keep their data out of the way of their code: scratch
+// cells at DATA_START and loop counters just after them.
+const DATA_START: i64 = 100;
+const DATA_SIZE: u64 = 16;
+const COUNTER_START: i64 = DATA_START + DATA_SIZE as i64;
+const COUNTERS: usize = 4;
+
+// Every address below this (or below the program length, if longer) is
+// compared after a run.
+const MEMORY_WINDOW: usize = 128;
+
+pub const MAX_STEPS: usize = 10000;
+
+// xorshift64*, seeded so that every finding can be regenerated from its seed.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed:u64) -> Rng {
+        Rng { state: (seed ^ 0x9e37_79b9_7f4a_7c15) | 1 }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+
+        self.state = x;
+
+        return x.wrapping_mul(0x2545_f491_4f6c_dd1d);
+    }
+
+    pub fn below(&mut self, n:u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    // Inclusive on both ends.
+    pub fn range(&mut self, low:i64, high:i64) -> i64 {
+        low + self.below((high - low + 1) as u64) as i64
+    }
+
+    pub fn one_in(&mut self, n:u64) -> bool {
+        self.below(n) == 0
+    }
+}
+
+#[derive(PartialEq,Debug)]
+pub enum Outcome {
+    Halted,
+    NeedsInput,
+    Error(IntcodeError),
+    StepLimit,
+}
+
+#[derive(PartialEq,Debug)]
+pub struct Execution {
+    pub outcome: Outcome,
+    pub outputs: Vec<i64>,
+    pub memory: Vec<Option<i64>>,
+}
+
+#[derive(PartialEq,Debug)]
+pub struct Finding {
+    pub seed: u64,
+    pub program: Vec<i64>,
+    pub inputs: Vec<i64>,
+    pub expected: Execution,
+    pub actual: Execution,
+}
+
+fn small_value(rng:&mut Rng) -> i64 {
+    if rng.one_in(8) {
+        return rng.range(-100000, 100000);
+    }
+
+    return rng.range(-20, 20);
+}
+
+fn data_address(rng:&mut Rng) -> i64 {
+    DATA_START + rng.below(DATA_SIZE) as i64
+}
+
+// (mode, value) of a parameter that is only read.
+fn read_operand(rng:&mut Rng) -> (i64, i64) {
+    match rng.below(3) {
+        0 => (0, data_address(rng)),
+        1 => (1, small_value(rng)),
+        _ => (2, data_address(rng)),
+    }
+}
+
+// Mostly writes to scratch cells, but now and then over the code emitted so
+// far, or in immediate mode, which the reference rejects.
+fn write_operand(rng:&mut Rng, code_size:usize) -> (i64, i64) {
+    if rng.one_in(16) {
+        return (1, data_address(rng));
+    }
+
+    if code_size > 0 && rng.one_in(8) {
+        return (0, rng.below(code_size as u64) as i64);
+    }
+
+    if rng.one_in(3) {
+        return (2, data_address(rng));
+    }
+
+    return (0, data_address(rng));
+}
+
+fn push_instruction(program:&mut Vec<i64>, opcode:i64, operands:&[(i64, i64)]) {
+    let mut code = opcode;
+    let mut factor = 100;
+
+    for &(mode, _) in operands {
+        code = code + mode * factor;
+        factor = factor * 10;
+    }
+
+    program.push(code);
+    program.extend(operands.iter().map(|&(_, value)| value));
+}
+
+// A single instruction that never jumps. Returns how many inputs it reads.
+fn push_straight_instruction(rng:&mut Rng, program:&mut Vec<i64>) -> usize {
+    let size = program.len();
+
+    match rng.below(7) {
+        0 => {
+            let operand = write_operand(rng, size);
+
+            push_instruction(program, 3, &[operand]);
+
+            return 1;
+        },
+        1 => {
+            let operand = read_operand(rng);
+
+            push_instruction(program, 4, &[operand]);
+        },
+        2 => {
+            let operand = if rng.one_in(4) { read_operand(rng) } else { (1, rng.range(-2, 2)) };
+
+            push_instruction(program, 9, &[operand]);
+        },
+        _ => {
+            let opcode = [1, 2, 7, 8][rng.below(4) as usize];
+            let operands = [read_operand(rng), read_operand(rng), write_operand(rng, size)];
+
+            push_instruction(program, opcode, &operands);
+        },
+    }
+
+    return 0;
+}
+
+// A conditional jump over one or two instructions.
+fn push_skip(rng:&mut Rng, program:&mut Vec<i64>) -> usize {
+    let opcode = if rng.one_in(2) { 5 } else { 6 };
+    let condition = read_operand(rng);
+
+    push_instruction(program, opcode, &[condition, (1, 0)]);
+
+    let target = program.len() - 1;
+    let mut inputs = 0;
+
+    for _ in 0..rng.range(1, 2) {
+        inputs = inputs + push_straight_instruction(rng, program);
+    }
+
+    program[target] = program.len() as i64;
+
+    return inputs;
+}
+
+// A loop body run a fixed number of times, guarded by its own counter so
+// it terminates unless the body overwrites the loop itself.
+fn push_loop(rng:&mut Rng, program:&mut Vec<i64>, counter:i64) -> usize {
+    let iterations = rng.range(1, 4);
+
+    push_instruction(program, 1, &[(1, iterations), (1, 0), (0, counter)]);
+
+    let start = program.len() as i64;
+    let mut inputs = 0;
+
+    for _ in 0..rng.range(1, 3) {
+        inputs = inputs + if rng.one_in(4) { push_skip(rng, program) } else { push_straight_instruction(rng, program) };
+    }
+
+    push_instruction(program, 1, &[(0, counter), (1, -1), (0, counter)]);
+    push_instruction(program, 5, &[(0, counter), (1, start)]);
+
+    return inputs * iterations as usize;
+}
+
+// A random program of straight-line code, forward jumps and bounded loops,
+// along with inputs for it. Sometimes there are fewer inputs than it reads.
+pub fn generate_program(rng:&mut Rng) -> (Vec<i64>, Vec<i64>) {
+    let mut program = vec![];
+    let mut inputs_read = 0;
+    let mut loops = 0;
+
+    for _ in 0..rng.range(1, 8) {
+        inputs_read = inputs_read + match rng.below(4) {
+            0 => {
+                loops = loops + 1;
+
+                push_loop(rng, &mut program, COUNTER_START + (loops % COUNTERS) as i64)
+            },
+            1 => push_skip(rng, &mut program),
+            _ => push_straight_instruction(rng, &mut program),
+        };
+    }
+
+    program.push(99);
+
+    let input_count = if rng.one_in(8) { rng.below(inputs_read as u64 + 1) as usize } else { inputs_read };
+    let inputs = (0..input_count).map(|_| small_value(rng)).collect();
+
+    return (program, inputs);
+}
+
+// Runs at most max_steps instructions, collecting outputs until the machine
+// halts, runs out of input or fails.
+pub fn execute_bounded<V: IntcodeVm>(program:&Vec<i64>, inputs:&[i64], max_steps:usize) -> Execution {
+    let mut vm = V::load(program.clone());
+    let mut outputs = vec![];
+    let mut outcome = Outcome::StepLimit;
+
+    for &value in inputs {
+        vm.push_input(value);
+    }
+
+    for _ in 0..max_steps {
+        match vm.step() {
+            Ok(None) => (),
+            Ok(Some(RunState::Output(value))) => outputs.push(value),
+            Ok(Some(RunState::Halted)) => {
+                outcome = Outcome::Halted;
+                break;
+            },
+            Ok(Some(RunState::NeedsInput)) => {
+                outcome = Outcome::NeedsInput;
+                break;
+            },
+            Err(error) => {
+                outcome = Outcome::Error(error);
+                break;
+            },
+        }
+    }
+
+    let memory = (0..cmp::max(program.len(), MEMORY_WINDOW)).map(|address| vm.read(address)).collect();
+
+    return Execution { outcome: outcome, outputs: outputs, memory: memory };
+}
+
+// The two executions when they disagree. A reference that overflows isn't
+// held against the variant, since Intcode<BigInt> is meant to keep going.
+pub fn differential<A: IntcodeVm, B: IntcodeVm>(program:&Vec<i64>, inputs:&[i64]) -> Option<(Execution, Execution)> {
+    let expected = execute_bounded::<A>(program, inputs, MAX_STEPS);
+    let actual = execute_bounded::<B>(program, inputs, MAX_STEPS);
+
+    if expected == actual {
+        return None;
+    }
+
+    if let Outcome::Error(IntcodeError::Overflow { .. }) = expected.outcome {
+        return None;
+    }
+
+    return Some((expected, actual));
+}
+
+fn simpler_values(value:i64) -> Vec<i64> {
+    if value == 0 {
+        return vec![];
+    }
+
+    if value / 2 == 0 {
+        return vec![0];
+    }
+
+    return vec![0, value / 2];
+}
+
+// Shrinks a failing (program, inputs) pair for as long as it keeps failing:
+// first by dropping runs of words and inputs, then by moving values towards
+// zero. Every accepted change makes the case strictly smaller, so this stops.
+pub fn minimize<F: Fn(&Vec<i64>, &Vec<i64>) -> bool>(program:&Vec<i64>, inputs:&Vec<i64>, fails:F) -> (Vec<i64>, Vec<i64>) {
+    let mut program = program.clone();
+    let mut inputs = inputs.clone();
+
+    'shrinking: loop {
+        let mut chunk = cmp::max(program.len() / 2, 1);
+
+        while chunk > 0 {
+            let mut start = 0;
+
+            while start + chunk <= program.len() {
+                let mut candidate = program.clone();
+
+                candidate.drain(start..start + chunk);
+
+                if fails(&candidate, &inputs) {
+                    program = candidate;
+                    continue 'shrinking;
+                }
+
+                start = start + chunk;
+            }
+
+            chunk = chunk / 2;
+        }
+
+        for i in 0..inputs.len() {
+            let mut candidate = inputs.clone();
+
+            candidate.remove(i);
+
+            if fails(&program, &candidate) {
+                inputs = candidate;
+                continue 'shrinking;
+            }
+        }
+
+        for i in 0..program.len() {
+            for value in simpler_values(program[i]) {
+                let mut candidate = program.clone();
+
+                candidate[i] = value;
+
+                if fails(&candidate, &inputs) {
+                    program = candidate;
+                    continue 'shrinking;
+                }
+            }
+        }
+
+        for i in 0..inputs.len() {
+            for value in simpler_values(inputs[i]) {
+                let mut candidate = inputs.clone();
+
+                candidate[i] = value;
+
+                if fails(&program, &candidate) {
+                    inputs = candidate;
+                    continue 'shrinking;
+                }
+            }
+        }
+
+        return (program, inputs);
+    }
+}
+
+// Generates a program per seed from seed onwards, wrapping around after
+// u64::MAX, and returns the first one on which the variant B disagrees with
+// the reference A, minimized.
+pub fn fuzz<A: IntcodeVm, B: IntcodeVm>(seed:u64, iterations:u64) -> Option<Finding> {
+    for i in 0..iterations {
+        let case_seed = seed.wrapping_add(i);
+        let (program, inputs) = generate_program(&mut Rng::new(case_seed));
+
+        if differential::<A, B>(&program, &inputs).is_none() {
+            continue;
+        }
+
+        let (program, inputs) = minimize(&program, &inputs, |program, inputs| differential::<A, B>(program, inputs).is_some());
+        let (expected, actual) = differential::<A, B>(&program, &inputs).unwrap();
+
+        return Some(Finding { seed: case_seed, program: program, inputs: inputs, expected: expected, actual: actual });
+    }
+
+    return None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigint::BigInt;
+    use compile::CompiledIntcode;
+    use machine::Intcode;
+
+    // Writes input in immediate mode to the address operand, the way the old
+    // day 9 interpreter did, instead of rejecting it
+    struct ImmediateInputVm(Intcode);
+
+    impl IntcodeVm for ImmediateInputVm {
+        fn load(program:Vec<i64>) -> ImmediateInputVm {
+            ImmediateInputVm(Intcode::new(program))
+        }
+
+        fn push_input(&mut self, value:i64) {
+            self.0.push_input(value);
+        }
+
+        fn step(&mut self) -> Result<Option<RunState>, IntcodeError> {
+            let pc = self.0.pc;
+
+            if self.0.read(pc) == 103 && !self.0.input.is_empty() {
+                let address = self.0.read(pc + 1);
+                let value = self.0.input.pop_front().unwrap();
+
+                self.0.write(address as usize, value);
+                self.0.pc = pc + 2;
+
+                return Ok(None);
+            }
+
+            return self.0.step();
+        }
+
+        fn read(&self, address:usize) -> Option<i64> {
+            Some(self.0.read(address))
+        }
+    }
+
+    #[test]
+    fn test_rng_is_deterministic() {
+        let first: Vec<u64> = (0..5).map({ let mut rng = Rng::new(7); move |_| rng.next_u64() }).collect();
+        let second: Vec<u64> = (0..5).map({ let mut rng = Rng::new(7); move |_| rng.next_u64() }).collect();
+
+        assert_eq!(first, second);
+        assert_eq!(generate_program(&mut Rng::new(3)), generate_program(&mut Rng::new(3)));
+        assert_ne!(generate_program(&mut Rng::new(3)), generate_program(&mut Rng::new(4)));
+    }
+
+    #[test]
+    fn test_generated_programs_terminate() {
+        let mut halted = 0;
+        let mut limited = 0;
+
+        for seed in 0..200 {
+            let (program, inputs) = generate_program(&mut Rng::new(seed));
+
+            match execute_bounded::<Intcode>(&program, &inputs, MAX_STEPS).outcome {
+                Outcome::Halted => halted = halted + 1,
+                Outcome::StepLimit => limited = limited + 1,
+                _ => (),
+            }
+        }
+
+        // Faults are expected now and then, and a program that overwrites its
+        // own loop can spin until the step limit, but most should run to the end
+        assert!(halted > 100, "{} halted", halted);
+        assert!(limited < 10, "{} hit the step limit", limited);
+    }
+
+    #[test]
+    fn test_no_differences() {
+        assert_eq!(fuzz::<Intcode, CompiledIntcode>(0, 500), None);
+        assert_eq!(fuzz::<Intcode, Intcode<BigInt>>(0, 500), None);
+        assert_eq!(fuzz::<Intcode, CompiledIntcode>(u64::MAX - 5, 10), None);
+    }
+
+    #[test]
+    fn test_finds_immediate_input_write() {
+        let finding = fuzz::<Intcode, ImmediateInputVm>(0, 500).unwrap();
+
+        assert_eq!(finding.program, vec![103]);
+        assert_eq!(finding.inputs, vec![0]);
+        assert_eq!(finding.expected.outcome, Outcome::Error(IntcodeError::ImmediateModeWrite { pc: 0 }));
+        assert_eq!(finding.actual.outcome, Outcome::Error(IntcodeError::InvalidOpcode { pc: 2, opcode: 0 }));
+    }
+
+    #[test]
+    fn test_minimize() {
+        // Fails whenever the program still contains a 7 and there is some input
+        let (program, inputs) = minimize(&vec![1,2,7,4,5,6], &vec![3,9], |program, inputs| program.contains(&7) && !inputs.is_empty());
+
+        assert_eq!(program, vec![7]);
+        assert_eq!(inputs, vec![0]);
+    }
+}
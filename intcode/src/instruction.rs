@@ -0,0 +1,211 @@
+use error::IntcodeError;
+use memory::Memory;
+use word::Word;
+
+#[derive(PartialEq,Debug)]
+pub enum ParameterMode {
+    Immediate,
+    Position,
+    Relative,
+}
+
+#[derive(PartialEq,Debug)]
+pub struct Instruction<W = i64> {
+    pub code: i64,
+    pub p1_mode: ParameterMode,
+    pub p2_mode: ParameterMode,
+    pub p3_mode: ParameterMode,
+    pub p1: W,
+    pub p2: W,
+    pub p3: W,
+    pub size: usize,
+}
+
+fn parse_parameter_mode(digit:i64, instruction:i64) -> Result<ParameterMode, IntcodeError> {
+    match digit {
+        0 => Ok(ParameterMode::Position),
+        1 => Ok(ParameterMode::Immediate),
+        2 => Ok(ParameterMode::Relative),
+        _ => Err(IntcodeError::InvalidParameterMode { instruction: instruction }),
+    }
+}
+
+pub fn parse_instruction_code(instruction:i64) -> Result<(ParameterMode, ParameterMode, ParameterMode, i64), IntcodeError> {
+    // At most three mode digits followed by a two digit opcode
+    if instruction > 99999 || instruction <= -10000 {
+        return Err(IntcodeError::InvalidParameterMode { instruction: instruction });
+    }
+
+    if instruction < 0 {
+        return Ok((ParameterMode::Position, ParameterMode::Position, ParameterMode::Position, instruction));
+    }
+
+    let p1_mode = parse_parameter_mode(instruction / 100 % 10, instruction)?;
+    let p2_mode = parse_parameter_mode(instruction / 1000 % 10, instruction)?;
+    let p3_mode = parse_parameter_mode(instruction / 10000, instruction)?;
+
+    return Ok((p1_mode, p2_mode, p3_mode, instruction % 100));
+}
+
+fn encode_parameter_mode(mode:&ParameterMode) -> i64 {
+    match *mode {
+        ParameterMode::Position => 0,
+        ParameterMode::Immediate => 1,
+        ParameterMode::Relative => 2,
+    }
+}
+
+// Inverse of parse_instruction_code for the parameters the instruction uses.
+// Modes of unused parameters are left as zero.
+pub fn encode_instruction_code<W>(instruction:&Instruction<W>) -> i64 {
+    let modes = [&instruction.p1_mode, &instruction.p2_mode, &instruction.p3_mode];
+    let mut code = instruction.code;
+    let mut factor = 100;
+
+    for mode in modes.iter().take(instruction.size - 1) {
+        code = code + factor * encode_parameter_mode(mode);
+        factor = factor * 10;
+    }
+
+    return code;
+}
+
+// Index (1-based, matching p1..p3) of the parameter the instruction writes to.
+pub fn get_write_parameter(code:i64) -> Option<usize> {
+    match code {
+        1 | 2 | 7 | 8 => Some(3),
+        3 => Some(1),
+        _ => None,
+    }
+}
+
+pub fn get_parameters<W: Clone>(instruction:&Instruction<W>) -> Vec<(&ParameterMode, W)> {
+    let parameters = vec![(&instruction.p1_mode, instruction.p1.clone()), (&instruction.p2_mode, instruction.p2.clone()), (&instruction.p3_mode, instruction.p3.clone())];
+
+    return parameters.into_iter().take(instruction.size - 1).collect();
+}
+
+pub fn get_instruction_at_index<W: Word>(idx:usize, memory:&Memory<W>) -> Result<Instruction<W>, IntcodeError> {
+    let code = match memory.read(idx).to_i64() {
+        Some(code) => code,
+        None => return Err(IntcodeError::Overflow { pc: idx }),
+    };
+
+    let (p1_mode, p2_mode, p3_mode, opcode) = parse_instruction_code(code)?;
+
+    let mut instruction: Instruction<W> = Instruction{
+        code: opcode,
+        p1: W::from_i64(0),
+        p2: W::from_i64(0),
+        p3: W::from_i64(0),
+        p1_mode: p1_mode,
+        p2_mode: p2_mode,
+        p3_mode: p3_mode,
+        size: 4,
+    };
+
+    if [1,2,7,8].contains(&instruction.code) {
+        instruction.p1 = memory.read(idx+1);
+        instruction.p2 = memory.read(idx+2);
+        instruction.p3 = memory.read(idx+3);
+    } else if [5,6].contains(&instruction.code) {
+        instruction.p1 = memory.read(idx+1);
+        instruction.p2 = memory.read(idx+2);
+        instruction.size = 3;
+    } else if [3,4,9].contains(&instruction.code) {
+        instruction.p1 = memory.read(idx+1);
+        instruction.size = 2;
+    } else if [99].contains(&instruction.code) {
+        instruction.size = 1;
+    } else {
+        return Err(IntcodeError::InvalidOpcode { pc: idx, opcode: instruction.code });
+    }
+
+    return Ok(instruction);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_instruction_code() {
+        let (p1_mode, p2_mode, p3_mode, opcode) = parse_instruction_code(1001).unwrap();
+
+        assert_eq!(p1_mode, ParameterMode::Position);
+        assert_eq!(p2_mode, ParameterMode::Immediate);
+        assert_eq!(p3_mode, ParameterMode::Position);
+        assert_eq!(opcode, 1);
+
+        let (p1_mode, p2_mode, p3_mode, opcode) = parse_instruction_code(11120).unwrap();
+
+        assert_eq!(p1_mode, ParameterMode::Immediate);
+        assert_eq!(p2_mode, ParameterMode::Immediate);
+        assert_eq!(p3_mode, ParameterMode::Immediate);
+        assert_eq!(opcode, 20);
+
+        let (p1_mode, p2_mode, p3_mode, opcode) = parse_instruction_code(3).unwrap();
+
+        assert_eq!(p1_mode, ParameterMode::Position);
+        assert_eq!(p2_mode, ParameterMode::Position);
+        assert_eq!(p3_mode, ParameterMode::Position);
+        assert_eq!(opcode, 3);
+
+        let (p1_mode, p2_mode, p3_mode, opcode) = parse_instruction_code(22201).unwrap();
+
+        assert_eq!(p1_mode, ParameterMode::Relative);
+        assert_eq!(p2_mode, ParameterMode::Relative);
+        assert_eq!(p3_mode, ParameterMode::Relative);
+        assert_eq!(opcode, 1);
+    }
+
+    #[test]
+    fn test_get_instruction_at_index() {
+        let instruction = get_instruction_at_index(4, &Memory::new(vec![0,2,4,5,1,2,3,4])).unwrap();
+
+        assert_eq!(instruction.code, 1);
+        assert_eq!(instruction.p1, 2);
+        assert_eq!(instruction.p2, 3);
+        assert_eq!(instruction.p3, 4);
+        assert_eq!(instruction.size, 4);
+
+        let instruction = get_instruction_at_index(0, &Memory::new(vec![109,19,99])).unwrap();
+
+        assert_eq!(instruction.code, 9);
+        assert_eq!(instruction.p1_mode, ParameterMode::Immediate);
+        assert_eq!(instruction.p1, 19);
+        assert_eq!(instruction.size, 2);
+    }
+
+    #[test]
+    fn test_encode_instruction_code() {
+        for &code in [1, 1001, 1101, 21101, 2, 3, 203, 4, 104, 204, 1005, 1106, 7, 21108, 9, 109, 99].iter() {
+            let instruction = get_instruction_at_index(0, &Memory::new(vec![code,0,0,0])).unwrap();
+
+            assert_eq!(encode_instruction_code(&instruction), code);
+        }
+
+        let instruction = get_instruction_at_index(0, &Memory::new(vec![10099])).unwrap();
+
+        assert_eq!(encode_instruction_code(&instruction), 99);
+    }
+
+    #[test]
+    fn test_parse_instruction_code_invalid_mode() {
+        assert_eq!(parse_instruction_code(301), Err(IntcodeError::InvalidParameterMode { instruction: 301 }));
+        assert_eq!(parse_instruction_code(100001), Err(IntcodeError::InvalidParameterMode { instruction: 100001 }));
+        assert_eq!(parse_instruction_code(-10000), Err(IntcodeError::InvalidParameterMode { instruction: -10000 }));
+        assert_eq!(parse_instruction_code(-9999), Ok((ParameterMode::Position, ParameterMode::Position, ParameterMode::Position, -9999)));
+    }
+
+    #[test]
+    fn test_get_instruction_at_index_invalid_opcode() {
+        let result = get_instruction_at_index(2, &Memory::new(vec![1101,1,42,0]));
+
+        assert_eq!(result, Err(IntcodeError::InvalidOpcode { pc: 2, opcode: 42 }));
+
+        let result = get_instruction_at_index(0, &Memory::new(vec![-1101,1,42,0]));
+
+        assert_eq!(result, Err(IntcodeError::InvalidOpcode { pc: 0, opcode: -1101 }));
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::mpsc::{Receiver, Sender};
+
+use budget::Budget;
+use error::IntcodeError;
+use machine::{parse_word, Intcode, RunState};
+use word::Word;
+
+// A source of input values. Returning None means the input is exhausted,
+// which stops a running machine with InputExhausted.
+pub trait IntcodeInput<W = i64> {
+    fn read_input(&mut self) -> Result<Option<W>, IntcodeError>;
+}
+
+pub trait IntcodeOutput<W = i64> {
+    fn write_output(&mut self, value:W) -> Result<(), IntcodeError>;
+}
+
+// Values are consumed from the front.
+impl<W> IntcodeInput<W> for Vec<W> {
+    fn read_input(&mut self) -> Result<Option<W>, IntcodeError> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+
+        return Ok(Some(self.remove(0)));
+    }
+}
+
+impl<W> IntcodeOutput<W> for Vec<W> {
+    fn write_output(&mut self, value:W) -> Result<(), IntcodeError> {
+        self.push(value);
+
+        Ok(())
+    }
+}
+
+impl<W> IntcodeInput<W> for VecDeque<W> {
+    fn read_input(&mut self) -> Result<Option<W>, IntcodeError> {
+        Ok(self.pop_front())
+    }
+}
+
+impl<W> IntcodeOutput<W> for VecDeque<W> {
+    fn write_output(&mut self, value:W) -> Result<(), IntcodeError> {
+        self.push_back(value);
+
+        Ok(())
+    }
+}
+
+// Blocks until a value arrives; a disconnected channel counts as exhausted.
+impl<W> IntcodeInput<W> for Receiver<W> {
+    fn read_input(&mut self) -> Result<Option<W>, IntcodeError> {
+        Ok(self.recv().ok())
+    }
+}
+
+impl<W> IntcodeOutput<W> for Sender<W> {
+    fn write_output(&mut self, value:W) -> Result<(), IntcodeError> {
+        match self.send(value) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(IntcodeError::IoError { message: "output channel closed".to_string() }),
+        }
+    }
+}
+
+// Reads one value per line. End of input or an empty line ends the input.
+pub struct StdinInput;
+
+impl<W: Word> IntcodeInput<W> for StdinInput {
+    fn read_input(&mut self) -> Result<Option<W>, IntcodeError> {
+        let mut input = String::new();
+
+        io::stdin().read_line(&mut input)?;
+
+        let input = input.trim();
+
+        if input.is_empty() {
+            return Ok(None);
+        }
+
+        return Ok(Some(parse_word(input)?));
+    }
+}
+
+pub struct StdoutOutput;
+
+impl<W: Word> IntcodeOutput<W> for StdoutOutput {
+    fn write_output(&mut self, value:W) -> Result<(), IntcodeError> {
+        io::stdout().write_fmt(format_args!("output: {}\n", value))?;
+        io::stdout().flush()?;
+
+        Ok(())
+    }
+}
+
+// Reads values separated by commas or whitespace, across any number of lines.
+pub struct ReaderInput<R> {
+    reader: R,
+    pending: VecDeque<String>,
+}
+
+impl<R: BufRead> ReaderInput<R> {
+    pub fn new(reader:R) -> ReaderInput<R> {
+        ReaderInput { reader: reader, pending: VecDeque::new() }
+    }
+}
+
+impl ReaderInput<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path:P) -> Result<ReaderInput<BufReader<File>>, IntcodeError> {
+        Ok(ReaderInput::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead, W: Word> IntcodeInput<W> for ReaderInput<R> {
+    fn read_input(&mut self) -> Result<Option<W>, IntcodeError> {
+        while self.pending.is_empty() {
+            let mut line = String::new();
+
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+
+            self.pending.extend(line.split(|c:char| c == ',' || c.is_whitespace()).filter(|token| !token.is_empty()).map(|token| token.to_string()));
+        }
+
+        let token = self.pending.pop_front().unwrap();
+
+        return Ok(Some(parse_word(&token)?));
+    }
+}
+
+// Writes one value per line.
+pub struct WriterOutput<T> {
+    writer: T,
+}
+
+impl<T: Write> WriterOutput<T> {
+    pub fn new(writer:T) -> WriterOutput<T> {
+        WriterOutput { writer: writer }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.writer
+    }
+}
+
+impl WriterOutput<File> {
+    pub fn create<P: AsRef<Path>>(path:P) -> Result<WriterOutput<File>, IntcodeError> {
+        Ok(WriterOutput::new(File::create(path)?))
+    }
+}
+
+impl<T: Write, W: Word> IntcodeOutput<W> for WriterOutput<T> {
+    fn write_output(&mut self, value:W) -> Result<(), IntcodeError> {
+        writeln!(self.writer, "{}", value)?;
+
+        Ok(())
+    }
+}
+
+pub struct InputFn<F>(pub F);
+
+impl<W, F: FnMut() -> Option<W>> IntcodeInput<W> for InputFn<F> {
+    fn read_input(&mut self) -> Result<Option<W>, IntcodeError> {
+        Ok((self.0)())
+    }
+}
+
+pub struct OutputFn<F>(pub F);
+
+impl<W, F: FnMut(W)> IntcodeOutput<W> for OutputFn<F> {
+    fn write_output(&mut self, value:W) -> Result<(), IntcodeError> {
+        (self.0)(value);
+
+        Ok(())
+    }
+}
+
+#[derive(Clone,Copy,PartialEq,Debug)]
+pub enum IoMode {
+    Numeric,
+    Ascii,
+}
+
+// A typed line becomes its character codes followed by a newline.
+pub fn encode_ascii_line(line:&str) -> Vec<i64> {
+    let mut codes: Vec<i64> = line.trim_end_matches(['\n', '\r']).chars().map(|c| c as i64).collect();
+
+    codes.push(10);
+
+    return codes;
+}
+
+// In ASCII mode, values outside 0..=127 are not characters and are written
+// as numbers on their own line.
+pub fn format_output(value:i64, mode:IoMode) -> String {
+    match mode {
+        IoMode::Ascii if (0..=127).contains(&value) => (value as u8 as char).to_string(),
+        IoMode::Ascii => format!("{}\n", value),
+        IoMode::Numeric => format!("output: {}\n", value),
+    }
+}
+
+// Runs the machine to completion, reading a line from the reader whenever it
+// needs input and writing each output to the writer.
+pub fn run_with_io<R: BufRead, W: Write>(machine:&mut Intcode, budget:&mut Budget, mode:IoMode, reader:&mut R, writer:&mut W) -> Result<(), IntcodeError> {
+    loop {
+        match budget.run_until_event(machine)? {
+            RunState::Halted => break,
+            RunState::Output(out) => writer.write_all(format_output(out, mode).as_bytes())?,
+            RunState::NeedsInput => {
+                writer.flush()?;
+
+                let mut line = String::new();
+
+                if reader.read_line(&mut line)? == 0 {
+                    return Err(IntcodeError::InputExhausted { pc: machine.pc });
+                }
+
+                match mode {
+                    IoMode::Ascii => {
+                        for code in encode_ascii_line(&line) {
+                            machine.push_input(code);
+                        }
+                    },
+                    IoMode::Numeric => match line.trim().parse::<i64>() {
+                        Ok(value) => machine.push_input(value),
+                        Err(_) => return Err(IntcodeError::ParseError { token: line.trim().to_string() }),
+                    },
+                };
+            },
+        };
+    }
+
+    writer.flush()?;
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+    use std::sync::mpsc::channel;
+    use std::thread;
+
+    // Echoes input characters until a newline, then outputs 1000
+    fn echo_line() -> Vec<i64> {
+        vec![3,100,4,100,1008,100,10,101,1006,101,0,104,1000,99]
+    }
+
+    #[test]
+    fn test_vec_io() {
+        let mut input = vec![1,2];
+        let mut output = vec![];
+
+        assert_eq!(input.read_input(), Ok(Some(1)));
+        assert_eq!(input.read_input(), Ok(Some(2)));
+        assert_eq!(input.read_input(), Ok(None));
+
+        output.write_output(3).unwrap();
+
+        assert_eq!(output, vec![3]);
+
+        let mut input: VecDeque<i64> = vec![4].into_iter().collect();
+
+        assert_eq!(input.read_input(), Ok(Some(4)));
+        assert_eq!(input.read_input(), Ok(None));
+    }
+
+    #[test]
+    fn test_reader_and_writer_io() {
+        let mut input = ReaderInput::new("1, 2\n\n-3 4,5\n".as_bytes());
+        let mut values = vec![];
+
+        while let Some(value) = IntcodeInput::<i64>::read_input(&mut input).unwrap() {
+            values.push(value);
+        }
+
+        assert_eq!(values, vec![1,2,-3,4,5]);
+
+        let mut input = ReaderInput::new("1,x".as_bytes());
+
+        assert_eq!(IntcodeInput::<i64>::read_input(&mut input), Ok(Some(1)));
+        assert_eq!(IntcodeInput::<i64>::read_input(&mut input), Err(IntcodeError::ParseError { token: "x".to_string() }));
+
+        let mut output = WriterOutput::new(vec![]);
+
+        output.write_output(7).unwrap();
+        output.write_output(-8).unwrap();
+
+        assert_eq!(String::from_utf8(output.into_inner()).unwrap(), "7\n-8\n");
+    }
+
+    #[test]
+    fn test_file_io() {
+        let path = env::temp_dir().join(format!("intcode-io-{}.txt", process::id()));
+
+        let mut output = WriterOutput::create(&path).unwrap();
+        let mut machine = Intcode::new(vec![104,1,104,2,99]);
+
+        machine.run(&mut vec![], &mut output).unwrap();
+        drop(output);
+
+        let mut input = ReaderInput::open(&path).unwrap();
+        let mut outputs = vec![];
+        let mut machine = Intcode::new(vec![3,0,3,1,1,0,1,0,4,0,99]);
+
+        machine.run(&mut input, &mut outputs).unwrap();
+
+        assert_eq!(outputs, vec![3]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_channel_io() {
+        let (to_machine, from_main) = channel();
+        let (to_main, from_machine) = channel();
+
+        let handle = thread::spawn(move || {
+            let mut receiver = from_main;
+            let mut sender = to_main;
+
+            Intcode::new(vec![3,11,1002,11,2,11,4,11,1105,1,0,0]).run(&mut receiver, &mut sender)
+        });
+
+        to_machine.send(5).unwrap();
+        assert_eq!(from_machine.recv(), Ok(10));
+
+        to_machine.send(-1).unwrap();
+        assert_eq!(from_machine.recv(), Ok(-2));
+
+        drop(to_machine);
+
+        assert_eq!(handle.join().unwrap(), Err(IntcodeError::InputExhausted { pc: 0 }));
+    }
+
+    #[test]
+    fn test_callback_io() {
+        let mut next = 0;
+        let mut total = 0;
+
+        {
+            let mut input = InputFn(|| { next = next + 1; if next <= 3 { Some(next) } else { None } });
+            let mut output = OutputFn(|value| total = total + value);
+
+            let result = Intcode::new(vec![3,7,4,7,1105,1,0,0]).run(&mut input, &mut output);
+
+            assert_eq!(result, Err(IntcodeError::InputExhausted { pc: 0 }));
+        }
+
+        assert_eq!(total, 6);
+    }
+
+    #[test]
+    fn test_encode_ascii_line() {
+        assert_eq!(encode_ascii_line("north"), vec![110,111,114,116,104,10]);
+        assert_eq!(encode_ascii_line("go\r\n"), vec![103,111,10]);
+        assert_eq!(encode_ascii_line(""), vec![10]);
+    }
+
+    #[test]
+    fn test_format_output() {
+        assert_eq!(format_output(72, IoMode::Ascii), "H");
+        assert_eq!(format_output(10, IoMode::Ascii), "\n");
+        assert_eq!(format_output(128, IoMode::Ascii), "128\n");
+        assert_eq!(format_output(-1, IoMode::Ascii), "-1\n");
+        assert_eq!(format_output(72, IoMode::Numeric), "output: 72\n");
+    }
+
+    #[test]
+    fn test_run_with_io_ascii() {
+        let mut machine = Intcode::new(echo_line());
+        let mut output = vec![];
+
+        run_with_io(&mut machine, &mut Budget::unlimited(), IoMode::Ascii, &mut "hi\nignored\n".as_bytes(), &mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "hi\n1000\n");
+    }
+
+    #[test]
+    fn test_run_with_io_numeric() {
+        let mut machine = Intcode::new(vec![3,0,4,0,99]);
+        let mut output = vec![];
+
+        run_with_io(&mut machine, &mut Budget::unlimited(), IoMode::Numeric, &mut " 7\n".as_bytes(), &mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "output: 7\n");
+
+        let mut machine = Intcode::new(vec![3,0,4,0,99]);
+
+        assert_eq!(run_with_io(&mut machine, &mut Budget::unlimited(), IoMode::Numeric, &mut "x\n".as_bytes(), &mut vec![]), Err(IntcodeError::ParseError { token: "x".to_string() }));
+    }
+
+    #[test]
+    fn test_run_with_io_end_of_input() {
+        let mut machine = Intcode::new(echo_line());
+        let mut output = vec![];
+
+        assert_eq!(run_with_io(&mut machine, &mut Budget::unlimited(), IoMode::Ascii, &mut "".as_bytes(), &mut output), Err(IntcodeError::InputExhausted { pc: 0 }));
+    }
+}
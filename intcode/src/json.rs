@@ -0,0 +1,363 @@
+use std::fmt;
+
+// Just enough JSON for the trace format: integers, strings, null, arrays
+// and objects with their keys kept in order.
+#[derive(PartialEq,Debug,Clone)]
+pub enum Json {
+    Null,
+    Number(i64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn get(&self, key:&str) -> Option<&Json> {
+        match *self {
+            Json::Object(ref fields) => fields.iter().find(|&&(ref k, _)| k == key).map(|&(_, ref v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Json::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Json>> {
+        match *self {
+            Json::Array(ref items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+// Arrays and objects nested deeper than this are rejected rather than
+// recursing until the stack runs out.
+const MAX_DEPTH: usize = 128;
+
+fn write_string(f: &mut fmt::Formatter, s:&str) -> fmt::Result {
+    write!(f, "\"")?;
+
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        };
+    }
+
+    write!(f, "\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Json::Null => write!(f, "null"),
+            Json::Number(n) => write!(f, "{}", n),
+            Json::String(ref s) => write_string(f, s),
+            Json::Array(ref items) => {
+                write!(f, "[")?;
+
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write!(f, "{}", item)?;
+                }
+
+                write!(f, "]")
+            },
+            Json::Object(ref fields) => {
+                write!(f, "{{")?;
+
+                for (i, &(ref key, ref value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+
+                write!(f, "}}")
+            },
+        }
+    }
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,
+    text: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.chars.len() && self.chars[self.pos].is_whitespace() {
+            self.pos = self.pos + 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.get(self.pos).cloned()
+    }
+
+    fn expect(&mut self, c:char) -> Result<(), String> {
+        if self.peek() == Some(c) {
+            self.pos = self.pos + 1;
+            return Ok(());
+        }
+
+        return Err(format!("expected {:?} at offset {} in {:?}", c, self.pos, self.text));
+    }
+
+    fn parse_value(&mut self) -> Result<Json, String> {
+        match self.peek() {
+            Some('[') | Some('{') if self.depth >= MAX_DEPTH => {
+                Err(format!("nested deeper than {} at offset {} in {:?}", MAX_DEPTH, self.pos, self.text))
+            },
+            Some('[') | Some('{') => {
+                self.depth = self.depth + 1;
+
+                let value = self.parse_container();
+
+                self.depth = self.depth - 1;
+
+                value
+            },
+            Some('n') => {
+                if self.chars[self.pos..].starts_with(&['n','u','l','l']) {
+                    self.pos = self.pos + 4;
+                    return Ok(Json::Null);
+                }
+
+                Err(format!("invalid literal at offset {} in {:?}", self.pos, self.text))
+            },
+            Some('"') => Ok(Json::String(self.parse_string()?)),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.pos;
+
+                self.pos = self.pos + 1;
+
+                while self.pos < self.chars.len() && self.chars[self.pos].is_ascii_digit() {
+                    self.pos = self.pos + 1;
+                }
+
+                let token: String = self.chars[start..self.pos].iter().collect();
+
+                match token.parse::<i64>() {
+                    Ok(n) => Ok(Json::Number(n)),
+                    Err(_) => Err(format!("invalid number {:?} in {:?}", token, self.text)),
+                }
+            },
+            _ => Err(format!("unexpected input at offset {} in {:?}", self.pos, self.text)),
+        }
+    }
+
+    fn parse_container(&mut self) -> Result<Json, String> {
+        match self.peek() {
+            Some('[') => {
+                self.expect('[')?;
+
+                let mut items = vec![];
+
+                if self.peek() == Some(']') {
+                    self.pos = self.pos + 1;
+                    return Ok(Json::Array(items));
+                }
+
+                loop {
+                    items.push(self.parse_value()?);
+
+                    if self.peek() == Some(',') {
+                        self.pos = self.pos + 1;
+                    } else {
+                        self.expect(']')?;
+                        return Ok(Json::Array(items));
+                    }
+                }
+            },
+            Some('{') => {
+                self.expect('{')?;
+
+                let mut fields = vec![];
+
+                if self.peek() == Some('}') {
+                    self.pos = self.pos + 1;
+                    return Ok(Json::Object(fields));
+                }
+
+                loop {
+                    self.skip_whitespace();
+
+                    let key = self.parse_string()?;
+
+                    self.expect(':')?;
+                    fields.push((key, self.parse_value()?));
+
+                    if self.peek() == Some(',') {
+                        self.pos = self.pos + 1;
+                    } else {
+                        self.expect('}')?;
+                        return Ok(Json::Object(fields));
+                    }
+                }
+            },
+            _ => Err(format!("unexpected input at offset {} in {:?}", self.pos, self.text)),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+
+        let mut s = String::new();
+
+        while self.pos < self.chars.len() {
+            let c = self.chars[self.pos];
+
+            self.pos = self.pos + 1;
+
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let escaped = match self.chars.get(self.pos) {
+                        Some(&'n') => '\n',
+                        Some(&'r') => '\r',
+                        Some(&'t') => '\t',
+                        Some(&'b') => '\u{8}',
+                        Some(&'f') => '\u{c}',
+                        Some(&'u') => {
+                            self.pos = self.pos + 1;
+                            s.push(self.parse_unicode_escape()?);
+                            continue;
+                        },
+                        Some(&c) => c,
+                        None => break,
+                    };
+
+                    s.push(escaped);
+                    self.pos = self.pos + 1;
+                },
+                _ => s.push(c),
+            };
+        }
+
+        return Err(format!("unterminated string in {:?}", self.text));
+    }
+
+    // The four hex digits after a \u, and the low half that has to follow a
+    // high surrogate.
+    fn parse_unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.parse_hex4()?;
+
+        let code = if (0xd800..0xdc00).contains(&high) && self.chars[self.pos..].starts_with(&['\\','u']) {
+            self.pos = self.pos + 2;
+
+            let low = self.parse_hex4()?;
+
+            if !(0xdc00..0xe000).contains(&low) {
+                return Err(format!("invalid surrogate pair at offset {} in {:?}", self.pos, self.text));
+            }
+
+            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+        } else {
+            high
+        };
+
+        return std::char::from_u32(code).ok_or(format!("invalid unicode escape at offset {} in {:?}", self.pos, self.text));
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, String> {
+        let digits: String = self.chars.iter().skip(self.pos).take(4).collect();
+
+        if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("invalid unicode escape at offset {} in {:?}", self.pos, self.text));
+        }
+
+        self.pos = self.pos + 4;
+
+        return u32::from_str_radix(&digits, 16).map_err(|e| e.to_string());
+    }
+}
+
+pub fn parse_json(text:&str) -> Result<Json, String> {
+    let mut parser = Parser { chars: text.chars().collect(), pos: 0, depth: 0, text: text };
+
+    let value = parser.parse_value()?;
+
+    if parser.peek().is_some() {
+        return Err(format!("trailing characters at offset {} in {:?}", parser.pos, text));
+    }
+
+    return Ok(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let value = Json::Object(vec![
+            ("op".to_string(), Json::String("ADD".to_string())),
+            ("params".to_string(), Json::Array(vec![Json::Number(-1), Json::Number(20)])),
+            ("write".to_string(), Json::Null),
+            ("empty".to_string(), Json::Array(vec![])),
+        ]);
+
+        let text = value.to_string();
+
+        assert_eq!(text, "{\"op\":\"ADD\",\"params\":[-1,20],\"write\":null,\"empty\":[]}");
+        assert_eq!(parse_json(&text), Ok(value));
+    }
+
+    #[test]
+    fn test_parse_whitespace_and_accessors() {
+        let value = parse_json(" { \"a\" : [ 1 , [2, 3] ] , \"b\": \"x\\\"y\" } ").unwrap();
+
+        assert_eq!(value.get("a").unwrap().as_array().unwrap()[0].as_i64(), Some(1));
+        assert_eq!(value.get("b"), Some(&Json::String("x\"y".to_string())));
+        assert_eq!(value.get("c"), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_json("{\"a\":1").is_err());
+        assert!(parse_json("[1,]").is_err());
+        assert!(parse_json("nul").is_err());
+        assert!(parse_json("1 2").is_err());
+        assert!(parse_json("\"\\u12\"").is_err());
+        assert!(parse_json("\"\\ud800\\u0041\"").is_err());
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let text = "quote \" backslash \\ newline \n tab \t bell \u{7} nul \u{0} é 🦀";
+        let value = Json::Object(vec![(text.to_string(), Json::String(text.to_string()))]);
+
+        let written = value.to_string();
+
+        assert!(written.contains("quote \\\" backslash \\\\ newline \\n tab \\t bell \\u0007 nul \\u0000"));
+        assert_eq!(parse_json(&written), Ok(value));
+        assert_eq!(parse_json("\"\\u00e9\\ud83e\\udd80\\/\""), Ok(Json::String("é🦀/".to_string())));
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let nested = |depth:usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+        assert!(parse_json(&nested(MAX_DEPTH)).is_ok());
+        assert!(parse_json(&nested(MAX_DEPTH + 1)).is_err());
+        assert!(parse_json(&"{\"a\":".repeat(100000)).is_err());
+    }
+}
@@ -0,0 +1,46 @@
+mod analyze;
+mod assemble;
+mod bigint;
+mod budget;
+mod compile;
+mod conformance;
+mod debugger;
+mod disassemble;
+mod error;
+mod fuzz;
+mod instruction;
+mod io;
+mod json;
+mod machine;
+mod memory;
+mod network;
+mod profile;
+mod search;
+mod snapshot;
+mod solve;
+mod symbolic;
+mod trace;
+mod word;
+
+pub use analyze::{analyze, format_dot, Analysis, BasicBlock};
+pub use assemble::{assemble, format_program, get_opcode};
+pub use bigint::BigInt;
+pub use budget::{execute_program_with_budget, Budget, BudgetLimit};
+pub use compile::CompiledIntcode;
+pub use conformance::{conformance_cases, run_conformance_case, run_conformance_suite, ConformanceCase, IntcodeVm};
+pub use debugger::{Debugger, StopReason};
+pub use disassemble::{decode_program, disassemble, format_instruction, format_parameter, get_jump_labels, get_mnemonic, Line};
+pub use error::IntcodeError;
+pub use fuzz::{differential, execute_bounded, fuzz, generate_program, minimize, Execution, Finding, Outcome, Rng, MAX_STEPS};
+pub use instruction::{encode_instruction_code, get_instruction_at_index, get_parameters, get_write_parameter, parse_instruction_code, Instruction, ParameterMode};
+pub use io::{encode_ascii_line, format_output, run_with_io, InputFn, IntcodeInput, IntcodeOutput, IoMode, OutputFn, ReaderInput, StdinInput, StdoutOutput, WriterOutput};
+pub use machine::{execute_instruction, execute_program, get_program_from_string, get_words_from_string, Intcode, Parameter, PendingInstruction, RunState};
+pub use memory::Memory;
+pub use network::{Network, Node};
+pub use profile::{MemoryHeatmap, Profiler};
+pub use search::{default_threads, permutations, search_permutations, SearchResult};
+pub use solve::{evaluate_patches, seek_goal, Patch, Solution, DEFAULT_MAX_INSTRUCTIONS};
+pub use snapshot::{load_snapshot, restore, save_snapshot, snapshot};
+pub use symbolic::{execute_symbolic, Condition, Expr, PathEnd, SymbolicPath};
+pub use trace::{parse_trace, replay_trace, step_with_trace, ReplayResult, TraceEvent, TraceRecorder};
+pub use word::Word;
@@ -0,0 +1,618 @@
+use std::collections::VecDeque;
+
+use error::IntcodeError;
+use instruction::{get_instruction_at_index, get_parameters, get_write_parameter, Instruction, ParameterMode};
+use io::{IntcodeInput, IntcodeOutput};
+use memory::Memory;
+use word::Word;
+
+#[derive(PartialEq,Debug)]
+pub enum RunState<W = i64> {
+    Halted,
+    NeedsInput,
+    Output(W),
+}
+
+// A parameter of an instruction about to execute: its raw value, the address
+// it refers to (None when immediate) and whether the instruction writes there.
+#[derive(Clone,PartialEq,Debug)]
+pub struct Parameter<W = i64> {
+    pub value: W,
+    pub address: Option<usize>,
+    pub write: bool,
+}
+
+#[derive(PartialEq,Debug)]
+pub struct PendingInstruction<W = i64> {
+    pub pc: usize,
+    pub instruction: Instruction<W>,
+    pub parameters: Vec<Parameter<W>>,
+}
+
+impl<W> PendingInstruction<W> {
+    pub fn write_address(&self) -> Option<usize> {
+        self.parameters.iter().find(|parameter| parameter.write).and_then(|parameter| parameter.address)
+    }
+}
+
+// The word type is picked at construction: Intcode::new runs on i64 and
+// reports overflow as an error, while Intcode::<BigInt>::from_words never
+// overflows.
+#[derive(Clone,PartialEq,Debug)]
+pub struct Intcode<W = i64> {
+    pub memory: Memory<W>,
+    pub pc: usize,
+    pub base: i64,
+    pub input: VecDeque<W>,
+}
+
+impl Intcode {
+    pub fn new(program:Vec<i64>) -> Intcode {
+        Intcode::from_words(program)
+    }
+}
+
+impl<W: Word> Intcode<W> {
+    pub fn from_words(program:Vec<W>) -> Intcode<W> {
+        Intcode {
+            memory: Memory::from_words(program),
+            pc: 0,
+            base: 0,
+            input: VecDeque::new(),
+        }
+    }
+
+    pub fn read(&self, address:usize) -> W {
+        self.memory.read(address)
+    }
+
+    pub fn write(&mut self, address:usize, value:W) {
+        self.memory.write(address, value);
+    }
+
+    pub fn push_input(&mut self, value:W) {
+        self.input.push_back(value);
+    }
+
+    // The state step stops in without executing the instruction, if any
+    fn get_stop(&self, instruction:&Instruction<W>) -> Option<RunState<W>> {
+        match instruction.code {
+            99 => Some(RunState::Halted),
+            3 if self.input.is_empty() => Some(RunState::NeedsInput),
+            _ => None,
+        }
+    }
+
+    // The instruction the next step executes, with its parameters resolved
+    // against the current relative base, so callers can see what it reads and
+    // writes before it runs. None when the next step halts or waits for input
+    // instead.
+    pub fn peek_instruction(&self) -> Result<Option<PendingInstruction<W>>, IntcodeError> {
+        let instruction = get_instruction_at_index(self.pc, &self.memory)?;
+
+        if self.get_stop(&instruction).is_some() {
+            return Ok(None);
+        }
+
+        let write_parameter = get_write_parameter(instruction.code);
+        let mut parameters = vec![];
+
+        for (i, (mode, value)) in get_parameters(&instruction).into_iter().enumerate() {
+            let write = write_parameter == Some(i + 1);
+
+            // Read parameters resolve their address the same way writes do
+            let address = if write || *mode != ParameterMode::Immediate {
+                Some(get_write_address(self.pc, mode, value.clone(), self.base)?)
+            } else {
+                None
+            };
+
+            parameters.push(Parameter { value: value, address: address, write: write });
+        }
+
+        return Ok(Some(PendingInstruction { pc: self.pc, instruction: instruction, parameters: parameters }));
+    }
+
+    // Executes a single instruction. Returns the event it produced, if any;
+    // halting and waiting for input leave the pc where it is. On error the pc
+    // is left on the faulting instruction.
+    pub fn step(&mut self) -> Result<Option<RunState<W>>, IntcodeError> {
+        let instruction = get_instruction_at_index(self.pc, &self.memory)?;
+
+        if let Some(state) = self.get_stop(&instruction) {
+            return Ok(Some(state));
+        }
+
+        let input = if instruction.code == 3 { self.input.pop_front() } else { None };
+
+        let size = instruction.size;
+
+        let (jump_address, relative_base, output) = execute_instruction(self.pc, instruction, &mut self.memory, self.base, input)?;
+
+        match relative_base {
+            None => (),
+            Some(b) => match self.base.checked_add(b) {
+                Some(base) => self.base = base,
+                None => return Err(IntcodeError::Overflow { pc: self.pc }),
+            },
+        };
+
+        self.pc = self.pc + size;
+
+        match jump_address {
+            None => (),
+            Some(counter) => self.pc = counter,
+        };
+
+        return Ok(output.map(RunState::Output));
+    }
+
+    // Executes instructions until the program halts, produces an output, or
+    // reads from an empty input queue. In the last case the pc is left on the
+    // input instruction so the machine resumes there once input is pushed.
+    pub fn run_until_event(&mut self) -> Result<RunState<W>, IntcodeError> {
+        loop {
+            if let Some(state) = self.step()? {
+                return Ok(state);
+            }
+        }
+    }
+
+    pub fn run(&mut self, input: &mut dyn IntcodeInput<W>, output: &mut dyn IntcodeOutput<W>) -> Result<(), IntcodeError> {
+        loop {
+            match self.run_until_event()? {
+                RunState::Halted => break,
+                RunState::NeedsInput => match input.read_input()? {
+                    Some(value) => self.push_input(value),
+                    None => return Err(IntcodeError::InputExhausted { pc: self.pc }),
+                },
+                RunState::Output(out) => output.write_output(out)?,
+            };
+        }
+
+        return Ok(());
+    }
+}
+
+pub(crate) fn parse_word<W: Word>(token:&str) -> Result<W, IntcodeError> {
+    match W::parse_word(token.trim()) {
+        Some(value) => Ok(value),
+        None => Err(IntcodeError::ParseError { token: token.to_string() }),
+    }
+}
+
+pub fn get_words_from_string<W: Word>(string:String) -> Result<Vec<W>, IntcodeError> {
+    let mut program:Vec<W> = vec![];
+
+    for token in string.split(","){
+        program.push(parse_word(token)?)
+     }
+
+    return Ok(program);
+}
+
+pub fn get_program_from_string(string:String) -> Result<Vec<i64>, IntcodeError> {
+    get_words_from_string(string)
+}
+
+pub fn execute_program(program:Vec<i64>, input: &mut dyn IntcodeInput, output: &mut dyn IntcodeOutput) -> Result<Vec<i64>, IntcodeError> {
+    let mut machine = Intcode::new(program);
+
+    machine.run(input, output)?;
+
+    return Ok(machine.memory.contiguous);
+}
+
+fn to_address<W: Word>(pc:usize, address:&W) -> Result<usize, IntcodeError> {
+    let address = match address.to_i64() {
+        Some(address) => address,
+        None => return Err(IntcodeError::Overflow { pc: pc }),
+    };
+
+    if address < 0 {
+        return Err(IntcodeError::NegativeAddress { pc: pc, address: address });
+    }
+
+    return Ok(address as usize);
+}
+
+fn to_relative_address<W: Word>(pc:usize, value:&W, base:i64) -> Result<usize, IntcodeError> {
+    match W::from_i64(base).checked_sum(value) {
+        Some(address) => to_address(pc, &address),
+        None => Err(IntcodeError::Overflow { pc: pc }),
+    }
+}
+
+pub(crate) fn read_parameter<W: Word>(pc:usize, mode:&ParameterMode, value:W, memory:&Memory<W>, base:i64) -> Result<W, IntcodeError> {
+    match *mode {
+        ParameterMode::Immediate => Ok(value),
+        ParameterMode::Position => Ok(memory.read(to_address(pc, &value)?)),
+        ParameterMode::Relative => Ok(memory.read(to_relative_address(pc, &value, base)?)),
+    }
+}
+
+pub(crate) fn get_write_address<W: Word>(pc:usize, mode:&ParameterMode, value:W, base:i64) -> Result<usize, IntcodeError> {
+    match *mode {
+        ParameterMode::Immediate => Err(IntcodeError::ImmediateModeWrite { pc: pc }),
+        ParameterMode::Position => to_address(pc, &value),
+        ParameterMode::Relative => to_relative_address(pc, &value, base),
+    }
+}
+
+// Jump target, relative base offset and output
+type Effects<W> = (Option<usize>, Option<i64>, Option<W>);
+
+// Returns the jump target, relative base offset and output produced by the
+// instruction, if any. Memory writes are applied in place.
+pub fn execute_instruction<W: Word>(pc:usize, instruction:Instruction<W>, memory:&mut Memory<W>, base:i64, input:Option<W>) -> Result<Effects<W>, IntcodeError> {
+    match instruction.code {
+        1 => {
+            // Add
+            let op1 = read_parameter(pc, &instruction.p1_mode, instruction.p1, memory, base)?;
+            let op2 = read_parameter(pc, &instruction.p2_mode, instruction.p2, memory, base)?;
+            let address = get_write_address(pc, &instruction.p3_mode, instruction.p3, base)?;
+
+            match op1.checked_sum(&op2) {
+                Some(sum) => memory.write(address, sum),
+                None => return Err(IntcodeError::Overflow { pc: pc }),
+            };
+        },
+        2 => {
+            // Multiply
+            let op1 = read_parameter(pc, &instruction.p1_mode, instruction.p1, memory, base)?;
+            let op2 = read_parameter(pc, &instruction.p2_mode, instruction.p2, memory, base)?;
+            let address = get_write_address(pc, &instruction.p3_mode, instruction.p3, base)?;
+
+            match op1.checked_product(&op2) {
+                Some(product) => memory.write(address, product),
+                None => return Err(IntcodeError::Overflow { pc: pc }),
+            };
+        },
+        3 => {
+            // Input
+            let val = match input {
+                None => return Err(IntcodeError::InputExhausted { pc: pc }),
+                Some(val) => val,
+            };
+
+            let address = get_write_address(pc, &instruction.p1_mode, instruction.p1, base)?;
+
+            memory.write(address, val);
+        },
+        4 => {
+            // Output
+            let output = read_parameter(pc, &instruction.p1_mode, instruction.p1, memory, base)?;
+
+            return Ok((None, None, Some(output)));
+        },
+        5 => {
+            // Jump-if-true
+            let p1 = read_parameter(pc, &instruction.p1_mode, instruction.p1, memory, base)?;
+            let p2 = read_parameter(pc, &instruction.p2_mode, instruction.p2, memory, base)?;
+
+            if !p1.is_zero() {
+                return Ok((Some(to_address(pc, &p2)?), None, None));
+            }
+        },
+        6 => {
+            // Jump-if-false
+            let p1 = read_parameter(pc, &instruction.p1_mode, instruction.p1, memory, base)?;
+            let p2 = read_parameter(pc, &instruction.p2_mode, instruction.p2, memory, base)?;
+
+            if p1.is_zero() {
+                return Ok((Some(to_address(pc, &p2)?), None, None));
+            }
+        },
+        7 => {
+            // Less-than
+            let p1 = read_parameter(pc, &instruction.p1_mode, instruction.p1, memory, base)?;
+            let p2 = read_parameter(pc, &instruction.p2_mode, instruction.p2, memory, base)?;
+            let address = get_write_address(pc, &instruction.p3_mode, instruction.p3, base)?;
+
+            memory.write(address, W::from_i64(if p1 < p2 {1} else {0}));
+        },
+        8 => {
+            // Equals
+            let p1 = read_parameter(pc, &instruction.p1_mode, instruction.p1, memory, base)?;
+            let p2 = read_parameter(pc, &instruction.p2_mode, instruction.p2, memory, base)?;
+            let address = get_write_address(pc, &instruction.p3_mode, instruction.p3, base)?;
+
+            memory.write(address, W::from_i64(if p1 == p2 {1} else {0}));
+        },
+        9 => {
+            // Relative base
+            let p1 = read_parameter(pc, &instruction.p1_mode, instruction.p1, memory, base)?;
+
+            match p1.to_i64() {
+                Some(offset) => return Ok((None, Some(offset), None)),
+                None => return Err(IntcodeError::Overflow { pc: pc }),
+            };
+        }
+        99 => (),
+        _ => return Err(IntcodeError::InvalidOpcode { pc: pc, opcode: instruction.code }),
+    };
+
+    return Ok((None, None, None));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigint::BigInt;
+    use io::ReaderInput;
+
+    fn no_input() -> Vec<i64> {
+        vec![]
+    }
+
+    #[test]
+    fn test_get_program_from_string() {
+        assert_eq!(get_program_from_string("1,0,0,3,99".to_string()).unwrap(), vec![1,0,0,3,99]);
+        assert_eq!(get_program_from_string("104,-5,99".to_string()).unwrap(), vec![104,-5,99]);
+    }
+
+    #[test]
+    fn test_execute_instruction() {
+        let mut memory = Memory::new(vec![1,0,0,0,99]);
+        let instruction = Instruction{code: 1, p1_mode: ParameterMode::Position, p2_mode: ParameterMode::Position, p3_mode: ParameterMode::Position, p1: 0, p2: 0, p3: 0, size: 4};
+
+        execute_instruction(0, instruction, &mut memory, 0, None).unwrap();
+
+        assert_eq!(memory.contiguous, vec![2,0,0,0,99]);
+
+        let mut memory = Memory::new(vec![2,3,0,3,99]);
+        let instruction = Instruction{code: 2, p1_mode: ParameterMode::Position, p2_mode: ParameterMode::Position, p3_mode: ParameterMode::Position, p1: 3, p2: 0, p3: 3, size: 4};
+
+        execute_instruction(0, instruction, &mut memory, 0, None).unwrap();
+
+        assert_eq!(memory.contiguous, vec![2,3,0,6,99]);
+
+        let mut memory = Memory::new(vec![2,4,4,5,99,0]);
+        let instruction = Instruction{code: 2, p1_mode: ParameterMode::Position, p2_mode: ParameterMode::Position, p3_mode: ParameterMode::Position, p1: 4, p2: 4, p3: 5, size: 4};
+
+        execute_instruction(0, instruction, &mut memory, 0, None).unwrap();
+
+        assert_eq!(memory.contiguous, vec![2,4,4,5,99,9801]);
+    }
+
+    #[test]
+    fn test_execute_program() {
+        let program = execute_program(vec![1,1,1,4,99,5,6,0,99], &mut no_input(), &mut vec![]).unwrap();
+
+        assert_eq!(program, vec![30,1,1,4,2,5,6,0,99]);
+
+        let program = execute_program(vec![1101,100,-1,4,0], &mut no_input(), &mut vec![]).unwrap();
+
+        assert_eq!(program, vec![1101,100,-1,4,99]);
+    }
+
+    #[test]
+    fn test_execute_program_relative_mode() {
+        let quine = vec![109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99];
+        let mut outputs = vec![];
+
+        execute_program(quine.clone(), &mut no_input(), &mut outputs).unwrap();
+
+        assert_eq!(outputs, quine);
+
+        let mut outputs = vec![];
+
+        execute_program(vec![1102,34915192,34915192,7,4,7,99,0], &mut no_input(), &mut outputs).unwrap();
+
+        assert_eq!(outputs, vec![1219070632396864]);
+
+        let mut outputs = vec![];
+
+        execute_program(vec![104,1125899906842624,99], &mut no_input(), &mut outputs).unwrap();
+
+        assert_eq!(outputs, vec![1125899906842624]);
+    }
+
+    #[test]
+    fn test_execute_program_relative_input() {
+        let mut outputs = vec![];
+
+        execute_program(vec![109,10,203,0,204,0,99], &mut vec![42], &mut outputs).unwrap();
+
+        assert_eq!(outputs, vec![42]);
+    }
+
+    #[test]
+    fn test_run_until_event() {
+        let mut machine = Intcode::new(vec![3,9,8,9,10,9,4,9,99,-1,8]);
+
+        assert_eq!(machine.run_until_event().unwrap(), RunState::NeedsInput);
+        assert_eq!(machine.run_until_event().unwrap(), RunState::NeedsInput);
+
+        machine.push_input(8);
+
+        assert_eq!(machine.run_until_event().unwrap(), RunState::Output(1));
+        assert_eq!(machine.run_until_event().unwrap(), RunState::Halted);
+        assert_eq!(machine.run_until_event().unwrap(), RunState::Halted);
+    }
+
+    #[test]
+    fn test_run_until_event_resumes_between_outputs() {
+        let mut machine = Intcode::new(vec![3,13,4,13,3,13,1001,13,1,13,4,13,99]);
+
+        machine.push_input(4);
+
+        assert_eq!(machine.run_until_event().unwrap(), RunState::Output(4));
+        assert_eq!(machine.run_until_event().unwrap(), RunState::NeedsInput);
+
+        machine.push_input(7);
+
+        assert_eq!(machine.run_until_event().unwrap(), RunState::Output(8));
+        assert_eq!(machine.run_until_event().unwrap(), RunState::Halted);
+    }
+
+    #[test]
+    fn test_step() {
+        let mut machine = Intcode::new(vec![1101,2,3,7,4,7,99,0]);
+
+        assert_eq!(machine.step().unwrap(), None);
+        assert_eq!(machine.pc, 4);
+        assert_eq!(machine.read(7), 5);
+        assert_eq!(machine.step().unwrap(), Some(RunState::Output(5)));
+        assert_eq!(machine.step().unwrap(), Some(RunState::Halted));
+        assert_eq!(machine.pc, 6);
+    }
+
+    #[test]
+    fn test_intcode_read_write() {
+        let mut machine = Intcode::new(vec![1,0,0,0,99]);
+
+        machine.write(1, 4);
+        machine.write(2000, 5);
+
+        assert_eq!(machine.read(1), 4);
+        assert_eq!(machine.read(2000), 5);
+        assert_eq!(machine.read(3000), 0);
+    }
+
+    #[test]
+    fn test_long_running_program() {
+        // Counts address 100 down from 100000, jumping back to 0 until it reaches zero
+        let mut machine = Intcode::new(vec![1001,100,-1,100,1005,100,0,4,100,99]);
+
+        machine.write(100, 100000);
+
+        assert_eq!(machine.run_until_event().unwrap(), RunState::Output(0));
+        assert_eq!(machine.run_until_event().unwrap(), RunState::Halted);
+    }
+
+    #[test]
+    fn test_get_program_from_string_parse_error() {
+        assert_eq!(get_program_from_string("1,x,3".to_string()), Err(IntcodeError::ParseError { token: "x".to_string() }));
+    }
+
+    #[test]
+    fn test_run_until_event_errors() {
+        let mut machine = Intcode::new(vec![1,0,0,0,42]);
+
+        assert_eq!(machine.run_until_event(), Err(IntcodeError::InvalidOpcode { pc: 4, opcode: 42 }));
+        assert_eq!(machine.pc, 4);
+
+        let mut machine = Intcode::new(vec![1,-1,0,0,99]);
+
+        assert_eq!(machine.run_until_event(), Err(IntcodeError::NegativeAddress { pc: 0, address: -1 }));
+
+        let mut machine = Intcode::new(vec![1101,1,1,5,99]);
+
+        assert_eq!(machine.run_until_event().unwrap(), RunState::Halted);
+
+        let mut machine = Intcode::new(vec![11101,1,1,5,99]);
+
+        assert_eq!(machine.run_until_event(), Err(IntcodeError::ImmediateModeWrite { pc: 0 }));
+
+        let mut machine = Intcode::new(vec![104,1,103,5,99]);
+        machine.push_input(3);
+
+        assert_eq!(machine.run_until_event().unwrap(), RunState::Output(1));
+        assert_eq!(machine.run_until_event(), Err(IntcodeError::ImmediateModeWrite { pc: 2 }));
+
+        let mut machine = Intcode::new(vec![1105,1,-3,99]);
+
+        assert_eq!(machine.run_until_event(), Err(IntcodeError::NegativeAddress { pc: 0, address: -3 }));
+    }
+
+    #[test]
+    fn test_execute_program_input_errors() {
+        assert_eq!(execute_program(vec![3,0,99], &mut no_input(), &mut vec![]), Err(IntcodeError::InputExhausted { pc: 0 }));
+
+        let mut bad_input = ReaderInput::new("seven\n".as_bytes());
+
+        assert_eq!(execute_program(vec![3,0,99], &mut bad_input, &mut vec![]), Err(IntcodeError::ParseError { token: "seven".to_string() }));
+    }
+
+    #[test]
+    fn test_execute_instruction_without_input() {
+        let mut memory = Memory::new(vec![3,0,99]);
+        let instruction = get_instruction_at_index(0, &memory).unwrap();
+
+        assert_eq!(execute_instruction(0, instruction, &mut memory, 0, None), Err(IntcodeError::InputExhausted { pc: 0 }));
+    }
+
+    #[test]
+    fn test_peek_instruction() {
+        // Adds [rb+2] and #5 into [9], after moving the relative base to 3
+        let mut machine = Intcode::new(vec![109,3,1201,2,5,9,3,0,99,0]);
+
+        machine.step().unwrap();
+
+        let pending = machine.peek_instruction().unwrap().unwrap();
+
+        assert_eq!(pending.pc, 2);
+        assert_eq!(pending.parameters, vec![
+            Parameter { value: 2, address: Some(5), write: false },
+            Parameter { value: 5, address: None, write: false },
+            Parameter { value: 9, address: Some(9), write: true },
+        ]);
+        assert_eq!(pending.write_address(), Some(9));
+
+        // Peeking doesn't execute anything
+        assert_eq!(machine.pc, 2);
+
+        machine.step().unwrap();
+
+        // Neither waiting for input nor halting executes an instruction
+        assert_eq!(machine.peek_instruction().unwrap(), None);
+        machine.push_input(1);
+        assert!(machine.peek_instruction().unwrap().is_some());
+        machine.step().unwrap();
+        assert_eq!(machine.peek_instruction().unwrap(), None);
+    }
+
+    #[test]
+    fn test_checked_overflow() {
+        let mut machine = Intcode::new(vec![1102,4611686018427387904,2,7,4,7,99,0]);
+
+        assert_eq!(machine.run_until_event(), Err(IntcodeError::Overflow { pc: 0 }));
+        assert_eq!(machine.pc, 0);
+
+        let mut machine = Intcode::new(vec![1101,9223372036854775807,1,7,4,7,99,0]);
+
+        assert_eq!(machine.run_until_event(), Err(IntcodeError::Overflow { pc: 0 }));
+
+        let mut machine = Intcode::new(vec![109,9223372036854775807,109,1,99]);
+
+        assert_eq!(machine.run_until_event(), Err(IntcodeError::Overflow { pc: 2 }));
+    }
+
+    #[test]
+    fn test_big_integer_mode() {
+        let program = get_words_from_string::<BigInt>("1102,4611686018427387904,2,7,4,7,99,0".to_string()).unwrap();
+        let mut machine = Intcode::from_words(program);
+
+        assert_eq!(machine.run_until_event().unwrap(), RunState::Output(BigInt::parse("9223372036854775808").unwrap()));
+        assert_eq!(machine.run_until_event().unwrap(), RunState::Halted);
+
+        let program = get_words_from_string::<BigInt>("3,11,2,11,11,11,4,11,99".to_string()).unwrap();
+        let mut machine = Intcode::from_words(program);
+
+        machine.push_input(BigInt::parse("123456789012345678901234567890").unwrap());
+
+        assert_eq!(machine.run_until_event().unwrap(), RunState::Output(BigInt::parse("15241578753238836750495351562536198787501905199875019052100").unwrap()));
+    }
+
+    #[test]
+    fn test_big_integer_mode_matches_i64() {
+        let quine = vec![109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99];
+        let mut machine = Intcode::from_words(quine.iter().map(|&value| BigInt::from_i64(value)).collect());
+        let mut outputs = vec![];
+
+        while let RunState::Output(out) = machine.run_until_event().unwrap() {
+            outputs.push(out.to_i64().unwrap());
+        }
+
+        assert_eq!(outputs, quine);
+    }
+
+    #[test]
+    fn test_big_integer_address_out_of_range() {
+        let program = get_words_from_string::<BigInt>("4,99999999999999999999,99".to_string()).unwrap();
+        let mut machine = Intcode::from_words(program);
+
+        assert_eq!(machine.run_until_event(), Err(IntcodeError::Overflow { pc: 0 }));
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use word::Word;
+
+// Writes up to this many words past the end of contiguous memory grow it in
+// place; anything further out is kept in the sparse large_memory map.
+const CONTIGUOUS_GROWTH_LIMIT: usize = 65536;
+
+#[derive(Clone,PartialEq,Debug)]
+pub struct Memory<W = i64> {
+    pub contiguous: Vec<W>,
+    pub large_memory: HashMap<usize,W>,
+}
+
+impl Memory {
+    pub fn new(program:Vec<i64>) -> Memory {
+        Memory::from_words(program)
+    }
+}
+
+impl<W: Word> Memory<W> {
+    pub fn from_words(program:Vec<W>) -> Memory<W> {
+        Memory {
+            contiguous: program,
+            large_memory: HashMap::new(),
+        }
+    }
+
+    pub fn read(&self, address:usize) -> W {
+        if address < self.contiguous.len() {
+            return self.contiguous[address].clone();
+        }
+
+        match self.large_memory.get(&address) {
+            Some(value) => value.clone(),
+            None => W::from_i64(0),
+        }
+    }
+
+    pub fn write(&mut self, address:usize, value:W) {
+        let length = self.contiguous.len();
+
+        if address < length {
+            self.contiguous[address] = value;
+        } else if address - length < CONTIGUOUS_GROWTH_LIMIT {
+            self.grow(address + 1);
+            self.contiguous[address] = value;
+        } else {
+            self.large_memory.insert(address, value);
+        }
+    }
+
+    // Number of words held, whether contiguous or sparse.
+    pub fn size(&self) -> usize {
+        self.contiguous.len() + self.large_memory.len()
+    }
+
+    // What size() would be after writing to address, without writing.
+    pub fn size_after_write(&self, address:usize) -> usize {
+        let length = self.contiguous.len();
+
+        if address < length {
+            return self.size();
+        }
+
+        if address - length < CONTIGUOUS_GROWTH_LIMIT {
+            let absorbed = self.large_memory.keys().filter(|&&key| key <= address).count();
+
+            return address + 1 + self.large_memory.len() - absorbed;
+        }
+
+        if self.large_memory.contains_key(&address) {
+            return self.size();
+        }
+
+        return self.size() + 1;
+    }
+
+    fn grow(&mut self, new_length:usize) {
+        self.contiguous.resize(new_length, W::from_i64(0));
+
+        if self.large_memory.is_empty() {
+            return;
+        }
+
+        let absorbed: Vec<usize> = self.large_memory.keys().cloned().filter(|&address| address < new_length).collect();
+
+        for address in absorbed {
+            if let Some(value) = self.large_memory.remove(&address) {
+                self.contiguous[address] = value;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read() {
+        let mut memory = Memory::new(vec![1,2,3]);
+        memory.large_memory.insert(1000000, 42);
+
+        assert_eq!(memory.read(2), 3);
+        assert_eq!(memory.read(1000000), 42);
+        assert_eq!(memory.read(5), 0);
+    }
+
+    #[test]
+    fn test_write_in_place() {
+        let mut memory = Memory::new(vec![1,2,3]);
+
+        memory.write(1, 7);
+
+        assert_eq!(memory.contiguous, vec![1,7,3]);
+        assert!(memory.large_memory.is_empty());
+    }
+
+    #[test]
+    fn test_write_grows_contiguous() {
+        let mut memory = Memory::new(vec![1,2,3]);
+
+        memory.write(6, 8);
+
+        assert_eq!(memory.contiguous, vec![1,2,3,0,0,0,8]);
+        assert!(memory.large_memory.is_empty());
+    }
+
+    #[test]
+    fn test_write_distant_address() {
+        let mut memory = Memory::new(vec![1,2,3]);
+
+        memory.write(10000000, 8);
+
+        assert_eq!(memory.contiguous.len(), 3);
+        assert_eq!(memory.large_memory[&10000000], 8);
+        assert_eq!(memory.read(10000000), 8);
+    }
+
+    #[test]
+    fn test_grow_absorbs_large_memory() {
+        let mut memory = Memory::new(vec![]);
+
+        memory.write(70000, 5);
+
+        assert_eq!(memory.contiguous.len(), 0);
+        assert_eq!(memory.large_memory[&70000], 5);
+
+        memory.write(60000, 6);
+        memory.write(100000, 7);
+
+        assert_eq!(memory.contiguous.len(), 100001);
+        assert!(memory.large_memory.is_empty());
+        assert_eq!(memory.read(60000), 6);
+        assert_eq!(memory.read(70000), 5);
+        assert_eq!(memory.read(100000), 7);
+    }
+
+    #[test]
+    fn test_size_after_write() {
+        let mut memory = Memory::new(vec![1,2,3]);
+
+        for &address in [1, 6, 70000, 70000, 100000, 60000, 200000].iter() {
+            let expected = memory.size_after_write(address);
+
+            memory.write(address, 9);
+
+            assert_eq!(memory.size(), expected, "writing {}", address);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use error::IntcodeError;
+use machine::{Intcode, RunState};
+
+pub struct Node {
+    pub machine: Intcode,
+    pub targets: Vec<usize>,
+    pub outputs: Vec<i64>,
+    pub halted: bool,
+    pub blocked: bool,
+}
+
+#[derive(Default)]
+pub struct Network {
+    pub nodes: Vec<Node>,
+}
+
+impl Network {
+    pub fn new() -> Network {
+        Network { nodes: vec![] }
+    }
+
+    pub fn add_machine(&mut self, program:Vec<i64>, initial_input:Vec<i64>) -> usize {
+        let mut machine = Intcode::new(program);
+
+        for value in initial_input {
+            machine.push_input(value);
+        }
+
+        self.nodes.push(Node {
+            machine: machine,
+            targets: vec![],
+            outputs: vec![],
+            halted: false,
+            blocked: false,
+        });
+
+        return self.nodes.len() - 1;
+    }
+
+    // Every output of `from` is delivered to the input queue of `to`. A node
+    // wired to several targets broadcasts to all of them.
+    pub fn connect(&mut self, from:usize, to:usize) {
+        self.nodes[from].targets.push(to);
+    }
+
+    pub fn connect_chain(&mut self, nodes:&[usize]) {
+        for pair in nodes.windows(2) {
+            self.connect(pair[0], pair[1]);
+        }
+    }
+
+    pub fn connect_ring(&mut self, nodes:&[usize]) {
+        self.connect_chain(nodes);
+
+        if nodes.len() > 1 {
+            self.connect(nodes[nodes.len() - 1], nodes[0]);
+        }
+    }
+
+    pub fn connect_star(&mut self, center:usize, leaves:&[usize]) {
+        for &leaf in leaves {
+            self.connect(center, leaf);
+            self.connect(leaf, center);
+        }
+    }
+
+    pub fn push_input(&mut self, node:usize, value:i64) {
+        self.nodes[node].machine.push_input(value);
+        self.nodes[node].blocked = false;
+    }
+
+    pub fn outputs(&self, node:usize) -> &Vec<i64> {
+        &self.nodes[node].outputs
+    }
+
+    pub fn is_halted(&self, node:usize) -> bool {
+        self.nodes[node].halted
+    }
+
+    pub fn all_halted(&self) -> bool {
+        self.nodes.iter().all(|node| node.halted)
+    }
+
+    // Runs one node until it halts or waits for input, delivering its outputs
+    // as they are produced.
+    fn run_node(&mut self, idx:usize) -> Result<(), IntcodeError> {
+        loop {
+            let state = match self.nodes[idx].machine.run_until_event() {
+                Ok(state) => state,
+                Err(error) => return Err(IntcodeError::NetworkNodeError { node: idx, error: Box::new(error) }),
+            };
+
+            match state {
+                RunState::Output(out) => {
+                    self.nodes[idx].outputs.push(out);
+
+                    for target in self.nodes[idx].targets.clone() {
+                        self.push_input(target, out);
+                    }
+                },
+                RunState::NeedsInput => {
+                    self.nodes[idx].blocked = true;
+                    return Ok(());
+                },
+                RunState::Halted => {
+                    self.nodes[idx].halted = true;
+                    return Ok(());
+                },
+            };
+        }
+    }
+
+    // Round-robin scheduler. Returns once every node has halted or is waiting
+    // for input that no other node can still provide.
+    pub fn run(&mut self) -> Result<(), IntcodeError> {
+        loop {
+            let mut progress = false;
+
+            for idx in 0..self.nodes.len() {
+                if !self.nodes[idx].halted && !self.nodes[idx].blocked {
+                    self.run_node(idx)?;
+                    progress = true;
+                }
+            }
+
+            if !progress {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Day 7 feedback loop example: max thruster signal 139629729 from phases 9,8,7,6,5
+    fn feedback_program() -> Vec<i64> {
+        vec![3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5]
+    }
+
+    #[test]
+    fn test_chain() {
+        // Day 7 amplifier example: max thruster signal 43210 from phases 4,3,2,1,0
+        let program = vec![3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0];
+        let mut network = Network::new();
+
+        let nodes: Vec<usize> = [4,3,2,1,0].iter().map(|&phase| network.add_machine(program.clone(), vec![phase])).collect();
+
+        network.connect_chain(&nodes);
+        network.push_input(nodes[0], 0);
+        network.run().unwrap();
+
+        assert_eq!(network.outputs(nodes[4]), &vec![43210]);
+        assert!(network.all_halted());
+    }
+
+    #[test]
+    fn test_ring() {
+        let mut network = Network::new();
+
+        let nodes: Vec<usize> = [9,8,7,6,5].iter().map(|&phase| network.add_machine(feedback_program(), vec![phase])).collect();
+
+        network.connect_ring(&nodes);
+        network.push_input(nodes[0], 0);
+        network.run().unwrap();
+
+        assert_eq!(network.outputs(nodes[4]).last(), Some(&139629729));
+        assert!(network.all_halted());
+    }
+
+    #[test]
+    fn test_star() {
+        // Leaves double whatever they receive; the center sums two inputs
+        let double = vec![3,9,1002,9,2,9,4,9,99,0];
+        let sum = vec![104,5,3,13,3,14,1,13,14,13,4,13,99,0,0];
+
+        let mut network = Network::new();
+        let center = network.add_machine(sum, vec![]);
+        let leaves = vec![network.add_machine(double.clone(), vec![]), network.add_machine(double, vec![])];
+
+        network.connect_star(center, &leaves);
+        network.run().unwrap();
+
+        assert_eq!(network.outputs(leaves[0]), &vec![10]);
+        assert_eq!(network.outputs(leaves[1]), &vec![10]);
+        assert_eq!(network.outputs(center), &vec![5,20]);
+        assert!(network.all_halted());
+    }
+
+    #[test]
+    fn test_quiescence_without_halting() {
+        let mut network = Network::new();
+        let a = network.add_machine(vec![3,0,4,0,99], vec![]);
+        let b = network.add_machine(vec![3,0,4,0,99], vec![]);
+
+        network.connect(a, b);
+        network.run().unwrap();
+
+        assert!(!network.is_halted(a));
+        assert!(!network.is_halted(b));
+
+        network.push_input(a, 7);
+        network.run().unwrap();
+
+        assert_eq!(network.outputs(b), &vec![7]);
+        assert!(network.all_halted());
+    }
+
+    #[test]
+    fn test_node_error() {
+        let mut network = Network::new();
+
+        network.add_machine(vec![99], vec![]);
+        network.add_machine(vec![42], vec![]);
+
+        assert_eq!(network.run(), Err(IntcodeError::NetworkNodeError { node: 1, error: Box::new(IntcodeError::InvalidOpcode { pc: 0, opcode: 42 }) }));
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::BTreeMap;
+
+use disassemble::{decode_program, format_instruction, get_jump_labels, get_mnemonic, Line};
+use error::IntcodeError;
+use machine::{Intcode, RunState};
+
+// Number of entries shown in each hot spot table of the report.
+const HOT_SPOT_COUNT: usize = 10;
+
+#[derive(Clone,PartialEq,Debug,Default)]
+pub struct MemoryHeatmap {
+    pub reads: BTreeMap<usize, u64>,
+    pub writes: BTreeMap<usize, u64>,
+}
+
+// Accesses are attributed to program memory or large_memory depending on
+// where the address lived when it was accessed.
+#[derive(Clone,PartialEq,Debug,Default)]
+pub struct Profiler {
+    pub cycles: u64,
+    pub pc_counts: BTreeMap<usize, u64>,
+    pub opcode_counts: BTreeMap<i64, u64>,
+    pub program_memory: MemoryHeatmap,
+    pub large_memory: MemoryHeatmap,
+}
+
+fn increment(counts:&mut BTreeMap<usize, u64>, address:usize) {
+    *counts.entry(address).or_insert(0) += 1;
+}
+
+fn percentage(count:u64, total:u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+
+    return 100.0 * count as f64 / total as f64;
+}
+
+// Highest counts first, lowest address first among equal counts.
+fn get_hottest(counts:&BTreeMap<usize, u64>) -> Vec<(usize, u64)> {
+    let mut hottest: Vec<(usize, u64)> = counts.iter().map(|(&address, &count)| (address, count)).collect();
+
+    hottest.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    hottest.truncate(HOT_SPOT_COUNT);
+
+    return hottest;
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+
+    fn heatmap(&mut self, machine:&Intcode, address:usize) -> &mut MemoryHeatmap {
+        if address < machine.memory.contiguous.len() {
+            &mut self.program_memory
+        } else {
+            &mut self.large_memory
+        }
+    }
+
+    // Reads are attributed before the instruction runs and its write after,
+    // so a write that grows program memory lands in program memory.
+    pub fn step(&mut self, machine:&mut Intcode) -> Result<Option<RunState>, IntcodeError> {
+        let pending = match machine.peek_instruction()? {
+            Some(pending) => pending,
+            None => return machine.step(),
+        };
+
+        for parameter in pending.parameters.iter().filter(|parameter| !parameter.write) {
+            if let Some(address) = parameter.address {
+                increment(&mut self.heatmap(machine, address).reads, address);
+            }
+        }
+
+        let state = machine.step()?;
+
+        if let Some(address) = pending.write_address() {
+            increment(&mut self.heatmap(machine, address).writes, address);
+        }
+
+        self.cycles = self.cycles + 1;
+        increment(&mut self.pc_counts, pending.pc);
+        *self.opcode_counts.entry(pending.instruction.code).or_insert(0) += 1;
+
+        return Ok(state);
+    }
+
+    pub fn run_until_event(&mut self, machine:&mut Intcode) -> Result<RunState, IntcodeError> {
+        loop {
+            if let Some(state) = self.step(machine)? {
+                return Ok(state);
+            }
+        }
+    }
+
+    // Renders the counts alongside a disassembly of the program, marking the
+    // most executed instructions.
+    pub fn format_report(&self, program:&Vec<i64>) -> String {
+        let mut output = format!("cycles: {}\n", self.cycles);
+
+        output.push_str("\nopcodes:\n");
+
+        let mut opcodes: Vec<(i64, u64)> = self.opcode_counts.iter().map(|(&code, &count)| (code, count)).collect();
+        opcodes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        for (code, count) in opcodes {
+            output.push_str(&format!("  {:<4} {:>12}  {:5.1}%\n", get_mnemonic(code).unwrap_or("???"), count, percentage(count, self.cycles)));
+        }
+
+        let hottest = get_hottest(&self.pc_counts);
+
+        output.push_str("\nhot spots:\n");
+
+        for &(pc, count) in &hottest {
+            output.push_str(&format!("  {:04} {:>12}  {:5.1}%\n", pc, count, percentage(count, self.cycles)));
+        }
+
+        let heatmaps = [("program memory", &self.program_memory), ("large_memory", &self.large_memory)];
+
+        for &(name, heatmap) in heatmaps.iter() {
+            for &(kind, counts) in [("reads", &heatmap.reads), ("writes", &heatmap.writes)].iter() {
+                if counts.is_empty() {
+                    continue;
+                }
+
+                let total: u64 = counts.values().sum();
+
+                output.push_str(&format!("\n{} {}: {} over {} addresses\n", name, kind, total, counts.len()));
+
+                for (address, count) in get_hottest(counts) {
+                    output.push_str(&format!("  [{}] {:>12}  {:5.1}%\n", address, count, percentage(count, total)));
+                }
+            }
+        }
+
+        output.push_str("\ndisassembly:\n");
+
+        let lines = decode_program(program);
+        let labels = get_jump_labels(&lines);
+
+        for line in &lines {
+            match *line {
+                Line::Code(address, ref instruction) => {
+                    if let Some(label) = labels.get(&address) {
+                        output.push_str(&format!("{:>22}{}:\n", "", label));
+                    }
+
+                    let count = *self.pc_counts.get(&address).unwrap_or(&0);
+                    let marker = if hottest.iter().any(|&(pc, _)| pc == address) { '*' } else { ' ' };
+
+                    let column = if count > 0 { format!("{:>12}  {:5.1}%", count, percentage(count, self.cycles)) } else { String::new() };
+
+                    output.push_str(&format!("{:<20}{} {:04}  {}\n", column, marker, address, format_instruction(instruction, &labels)));
+                },
+                Line::Data(address, ref words) => {
+                    let words: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+
+                    output.push_str(&format!("{:>22}{:04}  .data {}\n", "", address, words.join(", ")));
+                },
+            };
+        }
+
+        return output;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Counts address 100 down from 3, jumping back to 0 until it reaches zero
+    fn countdown() -> Vec<i64> {
+        vec![1001,100,-1,100,1005,100,0,4,100,99]
+    }
+
+    #[test]
+    fn test_counts() {
+        let mut machine = Intcode::new(countdown());
+        let mut profiler = Profiler::new();
+
+        machine.write(100, 3);
+
+        assert_eq!(profiler.run_until_event(&mut machine).unwrap(), RunState::Output(0));
+        assert_eq!(profiler.run_until_event(&mut machine).unwrap(), RunState::Halted);
+
+        assert_eq!(profiler.cycles, 7);
+        assert_eq!(profiler.pc_counts, vec![(0,3),(4,3),(7,1)].into_iter().collect());
+        assert_eq!(profiler.opcode_counts, vec![(1,3),(4,1),(5,3)].into_iter().collect());
+        assert_eq!(profiler.program_memory.reads, vec![(100,7)].into_iter().collect());
+        assert_eq!(profiler.program_memory.writes, vec![(100,3)].into_iter().collect());
+        assert!(profiler.large_memory.reads.is_empty());
+    }
+
+    #[test]
+    fn test_large_memory_accesses() {
+        let mut machine = Intcode::new(vec![109,1000000,21101,2,3,0,204,0,99]);
+        let mut profiler = Profiler::new();
+
+        assert_eq!(profiler.run_until_event(&mut machine).unwrap(), RunState::Output(5));
+
+        assert_eq!(profiler.large_memory.writes, vec![(1000000,1)].into_iter().collect());
+        assert_eq!(profiler.large_memory.reads, vec![(1000000,1)].into_iter().collect());
+        assert!(profiler.program_memory.writes.is_empty());
+    }
+
+    #[test]
+    fn test_waiting_for_input_is_not_counted() {
+        let mut machine = Intcode::new(vec![3,5,4,5,99,0]);
+        let mut profiler = Profiler::new();
+
+        assert_eq!(profiler.run_until_event(&mut machine).unwrap(), RunState::NeedsInput);
+        assert_eq!(profiler.cycles, 0);
+
+        machine.push_input(9);
+
+        assert_eq!(profiler.run_until_event(&mut machine).unwrap(), RunState::Output(9));
+        assert_eq!(profiler.cycles, 2);
+        assert_eq!(profiler.program_memory.writes, vec![(5,1)].into_iter().collect());
+    }
+
+    #[test]
+    fn test_format_report() {
+        let mut machine = Intcode::new(countdown());
+        let mut profiler = Profiler::new();
+
+        machine.write(100, 3);
+
+        while profiler.run_until_event(&mut machine).unwrap() != RunState::Halted {}
+
+        let report = profiler.format_report(&countdown());
+
+        assert!(report.starts_with("cycles: 7\n"));
+        assert!(report.contains("  ADD             3   42.9%\n"));
+        assert!(report.contains("\nprogram memory reads: 7 over 1 addresses\n  [100]            7  100.0%\n"));
+        assert!(report.contains("           3   42.9%* 0000  ADD [100], #-1 -> [100]\n"));
+        assert!(report.contains("                      L0:\n"));
+        assert!(report.contains("           1   14.3%* 0007  OUT [100]\n"));
+        assert!(report.contains("                      0009  HLT\n"));
+    }
+}
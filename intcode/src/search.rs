@@ -0,0 +1,182 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use error::IntcodeError;
+
+#[derive(PartialEq,Debug)]
+pub struct SearchResult {
+    pub best_phases: Vec<i64>,
+    pub best_output: i64,
+    pub ranking: Vec<(Vec<i64>, i64)>,
+}
+
+// Every ordered selection of `length` distinct items, in lexicographic order
+// of item positions.
+pub fn permutations(items:&[i64], length:usize) -> Vec<Vec<i64>> {
+    let mut result = vec![];
+    let mut current = vec![];
+    let mut used = vec![false; items.len()];
+
+    if length <= items.len() {
+        extend_permutation(items, length, &mut current, &mut used, &mut result);
+    }
+
+    return result;
+}
+
+fn extend_permutation(items:&[i64], length:usize, current:&mut Vec<i64>, used:&mut Vec<bool>, result:&mut Vec<Vec<i64>>) {
+    if current.len() == length {
+        result.push(current.clone());
+        return;
+    }
+
+    for i in 0..items.len() {
+        if used[i] {
+            continue;
+        }
+
+        used[i] = true;
+        current.push(items[i]);
+
+        extend_permutation(items, length, current, used, result);
+
+        current.pop();
+        used[i] = false;
+    }
+}
+
+pub fn default_threads() -> usize {
+    match thread::available_parallelism() {
+        Ok(n) => n.get(),
+        Err(_) => 1,
+    }
+}
+
+// Evaluates every permutation across `threads` workers. The ranking is
+// sorted by output, highest first, with ties broken by phase order so the
+// result doesn't depend on scheduling. The first error by permutation order
+// is returned if any evaluation fails.
+pub fn search_permutations<F>(phases:&[i64], length:usize, threads:usize, evaluate:F) -> Result<SearchResult, IntcodeError>
+    where F: Fn(&[i64]) -> Result<i64, IntcodeError> + Sync
+{
+    let candidates = permutations(phases, length);
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![]);
+
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| {
+                let mut local = vec![];
+
+                loop {
+                    let idx = next.fetch_add(1, Ordering::SeqCst);
+
+                    if idx >= candidates.len() {
+                        break;
+                    }
+
+                    local.push((idx, evaluate(&candidates[idx])));
+                }
+
+                results.lock().unwrap().extend(local);
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|&(idx, _)| idx);
+
+    let mut ranking = vec![];
+
+    for (idx, result) in results {
+        ranking.push((candidates[idx].clone(), result?));
+    }
+
+    ranking.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let (best_phases, best_output) = match ranking.first() {
+        Some(&(ref phases, output)) => (phases.clone(), output),
+        None => (vec![], 0),
+    };
+
+    return Ok(SearchResult {
+        best_phases: best_phases,
+        best_output: best_output,
+        ranking: ranking,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use network::Network;
+
+    fn run_amplifiers(program:&Vec<i64>, phases:&[i64]) -> Result<i64, IntcodeError> {
+        let mut network = Network::new();
+
+        let amplifiers: Vec<usize> = phases.iter().map(|&phase| network.add_machine(program.clone(), vec![phase])).collect();
+
+        network.connect_ring(&amplifiers);
+        network.push_input(amplifiers[0], 0);
+        network.run()?;
+
+        return Ok(*network.outputs(amplifiers[amplifiers.len() - 1]).last().unwrap_or(&0));
+    }
+
+    #[test]
+    fn test_permutations() {
+        assert_eq!(permutations(&[1,2,3], 3), vec![vec![1,2,3], vec![1,3,2], vec![2,1,3], vec![2,3,1], vec![3,1,2], vec![3,2,1]]);
+        assert_eq!(permutations(&[1,2,3], 2).len(), 6);
+        assert_eq!(permutations(&[0,1,2,3,4], 5).len(), 120);
+        assert_eq!(permutations(&[1,2], 0), vec![Vec::<i64>::new()]);
+        assert_eq!(permutations(&[1,2], 3), Vec::<Vec<i64>>::new());
+    }
+
+    #[test]
+    fn test_search_amplifier_chain() {
+        let program = vec![3,23,3,24,1002,24,10,24,1002,23,-1,23,101,5,23,23,1,24,23,23,4,23,99,0,0];
+
+        let result = search_permutations(&[0,1,2,3,4], 5, 4, |phases| run_amplifiers(&program, phases)).unwrap();
+
+        assert_eq!(result.best_phases, vec![0,1,2,3,4]);
+        assert_eq!(result.best_output, 54321);
+        assert_eq!(result.ranking.len(), 120);
+        assert!(result.ranking.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    }
+
+    #[test]
+    fn test_search_amplifier_feedback() {
+        let program = vec![3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,-5,54,1105,1,12,1,53,54,53,1008,54,0,55,1001,55,1,55,2,53,55,53,4,53,1001,56,-1,56,1005,56,6,99,0,0,0,0,10];
+
+        let result = search_permutations(&[5,6,7,8,9], 5, 3, |phases| run_amplifiers(&program, phases)).unwrap();
+
+        assert_eq!(result.best_phases, vec![9,7,8,5,6]);
+        assert_eq!(result.best_output, 18216);
+    }
+
+    #[test]
+    fn test_search_is_deterministic_across_thread_counts() {
+        let evaluate = |phases:&[i64]| Ok(phases[0] * phases[1] - phases[2]);
+
+        let single = search_permutations(&[1,2,3,4], 3, 1, evaluate).unwrap();
+        let many = search_permutations(&[1,2,3,4], 3, 8, evaluate).unwrap();
+
+        assert_eq!(single, many);
+        assert_eq!(single.best_phases, vec![3,4,1]);
+        assert_eq!(single.best_output, 11);
+    }
+
+    #[test]
+    fn test_search_error() {
+        let result = search_permutations(&[1,2,3], 3, 2, |phases| {
+            if phases[0] == 2 {
+                return Err(IntcodeError::InvalidOpcode { pc: 0, opcode: phases[1] });
+            }
+
+            Ok(0)
+        });
+
+        assert_eq!(result, Err(IntcodeError::InvalidOpcode { pc: 0, opcode: 1 }));
+    }
+}
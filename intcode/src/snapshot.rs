@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+
+use error::IntcodeError;
+use machine::Intcode;
+use memory::Memory;
+
+const MAGIC: &[u8] = b"ICS1";
+
+fn push_u64(bytes:&mut Vec<u8>, value:u64) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn push_i64(bytes:&mut Vec<u8>, value:i64) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count:usize) -> Result<&'a [u8], IntcodeError> {
+        if self.bytes.len() - self.pos < count {
+            return Err(IntcodeError::InvalidSnapshot { message: format!("truncated at byte {}", self.pos) });
+        }
+
+        let slice = &self.bytes[self.pos..self.pos + count];
+
+        self.pos = self.pos + count;
+
+        return Ok(slice);
+    }
+
+    fn read_u64(&mut self) -> Result<u64, IntcodeError> {
+        let mut word = [0; 8];
+
+        word.copy_from_slice(self.take(8)?);
+
+        return Ok(u64::from_le_bytes(word));
+    }
+
+    fn read_i64(&mut self) -> Result<i64, IntcodeError> {
+        let mut word = [0; 8];
+
+        word.copy_from_slice(self.take(8)?);
+
+        return Ok(i64::from_le_bytes(word));
+    }
+
+    fn read_length(&mut self) -> Result<usize, IntcodeError> {
+        let length = self.read_u64()? as usize;
+
+        // Every counted item is at least 8 bytes, so this rejects corrupt lengths before allocating
+        if length > (self.bytes.len() - self.pos) / 8 {
+            return Err(IntcodeError::InvalidSnapshot { message: format!("length {} at byte {} exceeds snapshot size", length, self.pos - 8) });
+        }
+
+        return Ok(length);
+    }
+}
+
+// Layout (little endian): magic, pc, relative base, contiguous memory,
+// large_memory entries sorted by address, then queued input.
+pub fn snapshot(machine:&Intcode) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+
+    push_u64(&mut bytes, machine.pc as u64);
+    push_i64(&mut bytes, machine.base);
+
+    push_u64(&mut bytes, machine.memory.contiguous.len() as u64);
+
+    for &word in &machine.memory.contiguous {
+        push_i64(&mut bytes, word);
+    }
+
+    let mut large_memory: Vec<(&usize, &i64)> = machine.memory.large_memory.iter().collect();
+    large_memory.sort();
+
+    push_u64(&mut bytes, large_memory.len() as u64);
+
+    for (&address, &value) in large_memory {
+        push_u64(&mut bytes, address as u64);
+        push_i64(&mut bytes, value);
+    }
+
+    push_u64(&mut bytes, machine.input.len() as u64);
+
+    for &value in &machine.input {
+        push_i64(&mut bytes, value);
+    }
+
+    return bytes;
+}
+
+pub fn restore(bytes:&[u8]) -> Result<Intcode, IntcodeError> {
+    let mut reader = Reader { bytes: bytes, pos: 0 };
+
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(IntcodeError::InvalidSnapshot { message: "missing snapshot header".to_string() });
+    }
+
+    let pc = reader.read_u64()? as usize;
+    let base = reader.read_i64()?;
+
+    let mut contiguous = vec![];
+
+    for _ in 0..reader.read_length()? {
+        contiguous.push(reader.read_i64()?);
+    }
+
+    let mut large_memory = HashMap::new();
+
+    for _ in 0..reader.read_length()? {
+        let pos = reader.pos;
+        let address = reader.read_u64()? as usize;
+
+        // Memory reads check contiguous first, so such an entry would be shadowed
+        if address < contiguous.len() {
+            return Err(IntcodeError::InvalidSnapshot { message: format!("large memory address {} at byte {} is inside contiguous memory", address, pos) });
+        }
+
+        if large_memory.insert(address, reader.read_i64()?).is_some() {
+            return Err(IntcodeError::InvalidSnapshot { message: format!("duplicate large memory address {} at byte {}", address, pos) });
+        }
+    }
+
+    let mut input = VecDeque::new();
+
+    for _ in 0..reader.read_length()? {
+        input.push_back(reader.read_i64()?);
+    }
+
+    if reader.pos != bytes.len() {
+        return Err(IntcodeError::InvalidSnapshot { message: format!("{} trailing bytes", bytes.len() - reader.pos) });
+    }
+
+    return Ok(Intcode {
+        memory: Memory { contiguous: contiguous, large_memory: large_memory },
+        pc: pc,
+        base: base,
+        input: input,
+    });
+}
+
+pub fn save_snapshot<P: AsRef<Path>>(machine:&Intcode, path:P) -> Result<(), IntcodeError> {
+    fs::write(path, snapshot(machine))?;
+
+    return Ok(());
+}
+
+pub fn load_snapshot<P: AsRef<Path>>(path:P) -> Result<Intcode, IntcodeError> {
+    return restore(&fs::read(path)?);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use machine::RunState;
+
+    // Day 9 quine: outputs a copy of itself, using relative mode and memory past the program
+    fn quine() -> Vec<i64> {
+        vec![109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99]
+    }
+
+    fn collect_outputs(machine:&mut Intcode) -> Vec<i64> {
+        let mut outputs = vec![];
+
+        while let RunState::Output(out) = machine.run_until_event().unwrap() {
+            outputs.push(out);
+        }
+
+        return outputs;
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut machine = Intcode::new(quine());
+
+        for _ in 0..5 {
+            machine.run_until_event().unwrap();
+        }
+
+        machine.write(1000000000, 7);
+        machine.push_input(3);
+        machine.push_input(4);
+
+        let mut restored = restore(&snapshot(&machine)).unwrap();
+
+        assert_eq!(restored, machine);
+        assert_eq!(collect_outputs(&mut restored), collect_outputs(&mut machine));
+    }
+
+    #[test]
+    fn test_clone_forks_machine() {
+        let mut machine = Intcode::new(vec![3,9,8,9,10,9,4,9,99,-1,8]);
+
+        assert_eq!(machine.run_until_event().unwrap(), RunState::NeedsInput);
+
+        let mut fork = machine.clone();
+
+        machine.push_input(8);
+        fork.push_input(7);
+
+        assert_eq!(machine.run_until_event().unwrap(), RunState::Output(1));
+        assert_eq!(fork.run_until_event().unwrap(), RunState::Output(0));
+    }
+
+    #[test]
+    fn test_restore_invalid_snapshot() {
+        let bytes = snapshot(&Intcode::new(vec![1,2,3]));
+
+        assert_eq!(restore(b"nope"), Err(IntcodeError::InvalidSnapshot { message: "missing snapshot header".to_string() }));
+        assert_eq!(restore(&bytes[..bytes.len() - 1]), Err(IntcodeError::InvalidSnapshot { message: "truncated at byte 60".to_string() }));
+        assert_eq!(restore(&bytes[..30]), Err(IntcodeError::InvalidSnapshot { message: "length 3 at byte 20 exceeds snapshot size".to_string() }));
+
+        let mut extended = bytes.clone();
+        extended.push(0);
+
+        assert_eq!(restore(&extended), Err(IntcodeError::InvalidSnapshot { message: "1 trailing bytes".to_string() }));
+    }
+
+    #[test]
+    fn test_restore_invalid_large_memory() {
+        let mut machine = Intcode::new(vec![1,2,3]);
+
+        machine.write(1000000000, 7);
+        machine.write(2000000000, 8);
+
+        // Entries start after the header, three words and the entry count
+        let bytes = snapshot(&machine);
+
+        let mut shadowed = bytes.clone();
+        shadowed[60..68].copy_from_slice(&2u64.to_le_bytes());
+
+        assert_eq!(restore(&shadowed), Err(IntcodeError::InvalidSnapshot { message: "large memory address 2 at byte 60 is inside contiguous memory".to_string() }));
+
+        let mut duplicate = bytes.clone();
+        duplicate[76..84].copy_from_slice(&1000000000u64.to_le_bytes());
+
+        assert_eq!(restore(&duplicate), Err(IntcodeError::InvalidSnapshot { message: "duplicate large memory address 1000000000 at byte 76".to_string() }));
+    }
+
+    #[test]
+    fn test_save_and_load_snapshot() {
+        let path = env::temp_dir().join(format!("intcode-snapshot-test-{}.bin", std::process::id()));
+        let mut machine = Intcode::new(quine());
+
+        machine.run_until_event().unwrap();
+        save_snapshot(&machine, &path).unwrap();
+
+        let mut loaded = load_snapshot(&path).unwrap();
+
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(collect_outputs(&mut loaded), collect_outputs(&mut machine));
+    }
+}
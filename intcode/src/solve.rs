@@ -0,0 +1,371 @@
+use std::convert::TryFrom;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use budget::Budget;
+use error::IntcodeError;
+use machine::Intcode;
+
+// Enough for day 2 style programs; candidates still running after the
+// limit given to seek_goal are treated like ones that fault.
+pub const DEFAULT_MAX_INSTRUCTIONS: u64 = 100000;
+
+// Values to try at one address of the program, e.g. the noun at address 1.
+#[derive(Clone,PartialEq,Debug)]
+pub struct Patch {
+    pub address: usize,
+    pub range: Range<i64>,
+}
+
+impl Patch {
+    pub fn new(address:usize, range:Range<i64>) -> Patch {
+        Patch { address: address, range: range }
+    }
+
+    // Saturates at usize::MAX, which seek_goal rejects as too many to try
+    fn len(&self) -> usize {
+        if self.range.end <= self.range.start {
+            return 0;
+        }
+
+        return usize::try_from(self.range.end as i128 - self.range.start as i128).unwrap_or(usize::MAX);
+    }
+}
+
+#[derive(PartialEq,Debug)]
+pub struct Solution {
+    // One value per patch, in the order the patches were given
+    pub values: Vec<i64>,
+    // How many times the program was run to find them
+    pub evaluations: usize,
+    // Whether the values were solved for rather than found by enumeration,
+    // in which case an earlier combination might also reach the goal
+    pub affine: bool,
+}
+
+// The value left at `address` once the patched program halts, or None if it
+// faults, waits for input or runs more than max_instructions.
+pub fn evaluate_patches(program:&Vec<i64>, patches:&[Patch], values:&[i64], address:usize, max_instructions:u64) -> Option<i64> {
+    let mut machine = Intcode::new(program.clone());
+    let mut budget = Budget::unlimited().with_max_instructions(max_instructions);
+
+    for (patch, &value) in patches.iter().zip(values) {
+        machine.write(patch.address, value);
+    }
+
+    match budget.run(&mut machine, &mut vec![], &mut vec![]) {
+        Ok(()) => Some(machine.read(address)),
+        Err(_) => None,
+    }
+}
+
+// The index-th combination of patch values, with the last patch varying fastest.
+fn get_values(patches:&[Patch], mut index:usize) -> Vec<i64> {
+    let mut values = vec![0; patches.len()];
+
+    for (i, patch) in patches.iter().enumerate().rev() {
+        values[i] = patch.range.start + (index % patch.len()) as i64;
+        index = index / patch.len();
+    }
+
+    return values;
+}
+
+struct AffineModel {
+    constant: i64,
+    coefficients: Vec<i64>,
+}
+
+impl AffineModel {
+    fn predict(&self, values:&[i64]) -> Option<i64> {
+        let mut total = self.constant;
+
+        for (coefficient, value) in self.coefficients.iter().zip(values) {
+            total = total.checked_add(coefficient.checked_mul(*value)?)?;
+        }
+
+        return Some(total);
+    }
+}
+
+// Probes the program at the low corner of the ranges and one step along each
+// patch to fit constant + sum(coefficient * value), then checks the fit
+// against every pair of steps, the high corner and the middle. Any probe that
+// fails or disagrees means the output isn't treated as affine.
+fn probe_affine(program:&Vec<i64>, patches:&[Patch], address:usize, max_instructions:u64, evaluations:&mut usize) -> Option<AffineModel> {
+    let low: Vec<i64> = patches.iter().map(|patch| patch.range.start).collect();
+    let mut evaluate = |values:&[i64]| {
+        *evaluations = *evaluations + 1;
+        evaluate_patches(program, patches, values, address, max_instructions)
+    };
+
+    let constant_at_low = evaluate(&low)?;
+    let mut coefficients = vec![];
+
+    for (i, patch) in patches.iter().enumerate() {
+        if patch.len() < 2 {
+            coefficients.push(0);
+            continue;
+        }
+
+        let mut values = low.clone();
+        values[i] = values[i] + 1;
+
+        coefficients.push(evaluate(&values)?.checked_sub(constant_at_low)?);
+    }
+
+    let mut constant = constant_at_low;
+
+    for (coefficient, value) in coefficients.iter().zip(&low) {
+        constant = constant.checked_sub(coefficient.checked_mul(*value)?)?;
+    }
+
+    let model = AffineModel { constant: constant, coefficients: coefficients };
+
+    let mut checks = vec![];
+
+    for i in 0..patches.len() {
+        for j in i + 1..patches.len() {
+            if patches[i].len() >= 2 && patches[j].len() >= 2 {
+                let mut values = low.clone();
+
+                values[i] = values[i] + 1;
+                values[j] = values[j] + 1;
+
+                checks.push(values);
+            }
+        }
+    }
+
+    checks.push(patches.iter().map(|patch| patch.range.end - 1).collect());
+    checks.push(patches.iter().map(|patch| patch.range.start + patch.len() as i64 / 2).collect());
+
+    for values in checks {
+        if evaluate(&values)? != model.predict(&values)? {
+            return None;
+        }
+    }
+
+    return Some(model);
+}
+
+// The first combination, in enumeration order, for which the model predicts
+// the target. All patches but the last one that affects the output are
+// enumerated; that one is solved for.
+fn solve_affine(model:&AffineModel, patches:&[Patch], target:i64) -> Option<Vec<i64>> {
+    let solved = match model.coefficients.iter().rposition(|&coefficient| coefficient != 0) {
+        Some(solved) => solved,
+        None if model.constant == target => return Some(get_values(patches, 0)),
+        None => return None,
+    };
+
+    // Patches after the solved one don't matter, so they stay at their lowest value
+    let enumerated = &patches[..solved];
+    let combinations = enumerated.iter().fold(1usize, |total, patch| total.saturating_mul(patch.len()));
+    let coefficient = model.coefficients[solved];
+
+    for index in 0..combinations {
+        let mut values = get_values(enumerated, index);
+
+        values.push(0);
+        values.extend(patches[solved + 1..].iter().map(|patch| patch.range.start));
+
+        let remainder = target.checked_sub(model.predict(&values)?)?;
+
+        // Dividing i64::MIN by -1 overflows, and no value in range solves it
+        if remainder.checked_rem(coefficient) != Some(0) {
+            continue;
+        }
+
+        values[solved] = match remainder.checked_div(coefficient) {
+            Some(value) => value,
+            None => continue,
+        };
+
+        if patches[solved].range.contains(&values[solved]) {
+            return Some(values);
+        }
+    }
+
+    return None;
+}
+
+// Finds values for the patched addresses that leave `target` at `address`
+// once the program halts, running each candidate for at most
+// max_instructions. When probing shows the result is affine in the patched
+// values, the answer is solved for and checked with a single run. It's the
+// first in enumeration order according to the model, but probes can't prove
+// the output affine everywhere, so an earlier combination may also work;
+// such solutions are marked `affine`. Otherwise combinations are run across
+// `threads` workers and the first one in enumeration order (last patch
+// varying fastest) is returned, no matter how many threads search. When the
+// model finds nothing the combinations are still enumerated.
+pub fn seek_goal(program:&Vec<i64>, patches:&[Patch], address:usize, target:i64, threads:usize, max_instructions:u64) -> Result<Option<Solution>, IntcodeError> {
+    let mut evaluations = 0;
+
+    if let Some(patch) = patches.iter().find(|patch| patch.len() == usize::MAX) {
+        return Err(IntcodeError::InvalidPatch { address: patch.address, message: format!("too many values in {:?}", patch.range) });
+    }
+
+    if patches.iter().any(|patch| patch.len() == 0) {
+        return Ok(None);
+    }
+
+    if let Some(model) = probe_affine(program, patches, address, max_instructions, &mut evaluations) {
+        if let Some(values) = solve_affine(&model, patches, target) {
+            evaluations = evaluations + 1;
+
+            if evaluate_patches(program, patches, &values, address, max_instructions) == Some(target) {
+                return Ok(Some(Solution { values: values, evaluations: evaluations, affine: true }));
+            }
+        }
+    }
+
+    let combinations = patches.iter().fold(1usize, |total, patch| total.saturating_mul(patch.len()));
+    let next = AtomicUsize::new(0);
+    let found = AtomicUsize::new(usize::MAX);
+    let runs = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+
+                    if index >= combinations || index > found.load(Ordering::SeqCst) {
+                        break;
+                    }
+
+                    runs.fetch_add(1, Ordering::SeqCst);
+
+                    if evaluate_patches(program, patches, &get_values(patches, index), address, max_instructions) == Some(target) {
+                        found.fetch_min(index, Ordering::SeqCst);
+                    }
+                }
+            });
+        }
+    });
+
+    let index = found.into_inner();
+
+    if index == usize::MAX {
+        return Ok(None);
+    }
+
+    return Ok(Some(Solution { values: get_values(patches, index), evaluations: evaluations + runs.into_inner(), affine: false }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Leaves 100 * noun + verb + 7 at address 0, in the style of day 2
+    fn affine_program() -> Vec<i64> {
+        vec![1102,0,100,13,1001,13,0,13,1001,13,7,0,99,0]
+    }
+
+    fn affine_patches() -> [Patch; 2] {
+        [Patch::new(1, 0..100), Patch::new(6, 0..100)]
+    }
+
+    // Leaves noun * verb at address 0, which isn't affine
+    fn product_program() -> Vec<i64> {
+        vec![1102,0,0,0,99]
+    }
+
+    #[test]
+    fn test_get_values() {
+        let patches = [Patch::new(1, 0..3), Patch::new(2, 10..12)];
+
+        assert_eq!(get_values(&patches, 0), vec![0,10]);
+        assert_eq!(get_values(&patches, 1), vec![0,11]);
+        assert_eq!(get_values(&patches, 5), vec![2,11]);
+    }
+
+    #[test]
+    fn test_evaluate_patches() {
+        assert_eq!(evaluate_patches(&affine_program(), &affine_patches(), &[12,2], 0, DEFAULT_MAX_INSTRUCTIONS), Some(1209));
+        // Jumps to itself forever once patched
+        assert_eq!(evaluate_patches(&vec![1105,1,0], &[Patch::new(2, 0..1)], &[0], 0, DEFAULT_MAX_INSTRUCTIONS), None);
+        assert_eq!(evaluate_patches(&vec![3,0,99], &[], &[], 0, DEFAULT_MAX_INSTRUCTIONS), None);
+    }
+
+    #[test]
+    fn test_affine_goal() {
+        let patches = affine_patches();
+        let solution = seek_goal(&affine_program(), &patches, 0, 4213, 4, DEFAULT_MAX_INSTRUCTIONS).unwrap().unwrap();
+
+        assert_eq!(solution.values, vec![42,6]);
+        assert!(solution.affine);
+        assert!(solution.evaluations < 10);
+
+        assert_eq!(seek_goal(&affine_program(), &patches, 0, 5, 4, DEFAULT_MAX_INSTRUCTIONS).unwrap(), None);
+    }
+
+    #[test]
+    fn test_enumerated_goal() {
+        let patches = [Patch::new(1, 1..50), Patch::new(2, 1..50)];
+        let solution = seek_goal(&product_program(), &patches, 0, 36, 4, DEFAULT_MAX_INSTRUCTIONS).unwrap().unwrap();
+
+        // 36 is also 4 * 9, 6 * 6, ..., but 1 * 36 comes first
+        assert_eq!(solution.values, vec![1,36]);
+        assert!(!solution.affine);
+
+        assert_eq!(seek_goal(&product_program(), &patches, 0, 37 * 53, 4, DEFAULT_MAX_INSTRUCTIONS).unwrap(), None);
+    }
+
+    #[test]
+    fn test_result_does_not_depend_on_threads() {
+        let patches = [Patch::new(1, 2..50), Patch::new(2, 2..50)];
+
+        for threads in 1..5 {
+            assert_eq!(seek_goal(&product_program(), &patches, 0, 36, threads, DEFAULT_MAX_INSTRUCTIONS).unwrap().unwrap().values, vec![2,18]);
+        }
+    }
+
+    #[test]
+    fn test_three_patches() {
+        // Leaves a + 10 * b + 100 * c at address 0, with a patch that only
+        // takes one value
+        let program = vec![1102,0,10,20,1102,0,100,21,1001,20,0,20,1,20,21,0,99];
+        let patches = [Patch::new(10, 0..10), Patch::new(1, 0..10), Patch::new(5, 0..10), Patch::new(16, 99..100)];
+
+        let solution = seek_goal(&program, &patches, 0, 987, 2, DEFAULT_MAX_INSTRUCTIONS).unwrap().unwrap();
+
+        assert_eq!(solution.values, vec![7,8,9,99]);
+        assert!(solution.affine);
+    }
+
+    #[test]
+    fn test_faulting_candidates_are_skipped() {
+        // Only patching in 99 at address 0 makes the program halt before
+        // reaching the invalid opcode
+        let patches = [Patch::new(0, 90..100)];
+
+        assert_eq!(seek_goal(&vec![1,0,0,0,42], &patches, 1, 0, 2, DEFAULT_MAX_INSTRUCTIONS).unwrap().unwrap().values, vec![99]);
+    }
+
+    #[test]
+    fn test_instruction_limit() {
+        // Counts [8] down from 20 before halting, taking 40 instructions
+        let program = vec![1001,8,-1,8,1005,8,0,99,20];
+
+        assert_eq!(evaluate_patches(&program, &[], &[], 8, 40), Some(0));
+        assert_eq!(evaluate_patches(&program, &[], &[], 8, 39), None);
+    }
+
+    #[test]
+    fn test_extreme_ranges() {
+        let patches = [Patch::new(1, i64::MIN..i64::MAX)];
+
+        assert!(seek_goal(&vec![1101,0,0,0,99], &patches, 0, 5, 1, DEFAULT_MAX_INSTRUCTIONS).is_err());
+
+        // Leaves -[1] at address 0, so i64::MIN can only be reached by
+        // dividing by -1, which overflows
+        let model = AffineModel { constant: 0, coefficients: vec![-1] };
+
+        assert_eq!(solve_affine(&model, &[Patch::new(1, -10..10)], i64::MIN), None);
+        assert_eq!(solve_affine(&model, &[Patch::new(1, -10..10)], 3), Some(vec![-3]));
+    }
+}
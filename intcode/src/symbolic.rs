@@ -0,0 +1,536 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use error::IntcodeError;
+use instruction::{get_instruction_at_index, get_parameters, get_write_parameter, ParameterMode};
+use machine::{execute_instruction, get_write_address, read_parameter};
+use memory::Memory;
+use word::Word;
+
+// A value computed from symbolic memory cells and inputs. The constructors
+// keep expressions in a simple normal form: constants are folded and moved
+// to the right, and multiplication by a constant is distributed over
+// addition, so affine programs come out as a sum of scaled symbols.
+#[derive(Clone,PartialEq,PartialOrd,Debug)]
+pub enum Expr {
+    Const(i64),
+    Symbol(String),
+    // The value read through an address that is itself symbolic
+    Load(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    LessThan(Box<Expr>, Box<Expr>),
+    Equals(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    pub fn symbol(name:&str) -> Expr {
+        Expr::Symbol(name.to_string())
+    }
+
+    // None when two constants overflow, like the i64 machine.
+    pub fn sum(a:&Expr, b:&Expr) -> Option<Expr> {
+        match (a, b) {
+            (&Expr::Const(x), &Expr::Const(y)) => x.checked_add(y).map(Expr::Const),
+            (&Expr::Const(0), _) => Some(b.clone()),
+            (_, &Expr::Const(0)) => Some(a.clone()),
+            (&Expr::Const(_), _) => Expr::sum(b, a),
+            (&Expr::Add(ref x, ref c), &Expr::Const(_)) if c.is_const() => Some(Expr::Add(x.clone(), Box::new(Expr::sum(c, b)?))),
+            (_, &Expr::Add(ref y, ref c)) if c.is_const() => Expr::sum(&Expr::sum(a, y)?, c),
+            (&Expr::Add(ref x, ref c), _) if c.is_const() => Expr::sum(&Expr::sum(x, b)?, c),
+            _ => Some(Expr::Add(Box::new(a.clone()), Box::new(b.clone()))),
+        }
+    }
+
+    pub fn product(a:&Expr, b:&Expr) -> Option<Expr> {
+        match (a, b) {
+            (&Expr::Const(x), &Expr::Const(y)) => x.checked_mul(y).map(Expr::Const),
+            (&Expr::Const(0), _) | (_, &Expr::Const(0)) => Some(Expr::Const(0)),
+            (&Expr::Const(1), _) => Some(b.clone()),
+            (_, &Expr::Const(1)) => Some(a.clone()),
+            (&Expr::Const(_), _) => Expr::product(b, a),
+            (&Expr::Mul(ref x, ref c), &Expr::Const(_)) if c.is_const() => Some(Expr::Mul(x.clone(), Box::new(Expr::product(c, b)?))),
+            (&Expr::Add(ref x, ref y), &Expr::Const(_)) => Expr::sum(&Expr::product(x, b)?, &Expr::product(y, b)?),
+            _ => Some(Expr::Mul(Box::new(a.clone()), Box::new(b.clone()))),
+        }
+    }
+
+    pub fn less_than(a:&Expr, b:&Expr) -> Expr {
+        match (a, b) {
+            (&Expr::Const(x), &Expr::Const(y)) => Expr::Const(if x < y {1} else {0}),
+            _ => Expr::LessThan(Box::new(a.clone()), Box::new(b.clone())),
+        }
+    }
+
+    // Two reads of the same address can differ if there was a write in
+    // between, so only expressions without loads are equal to themselves.
+    pub fn equals(a:&Expr, b:&Expr) -> Expr {
+        match (a, b) {
+            (&Expr::Const(x), &Expr::Const(y)) => Expr::Const(if x == y {1} else {0}),
+            _ if a == b && !a.has_load() => Expr::Const(1),
+            _ => Expr::Equals(Box::new(a.clone()), Box::new(b.clone())),
+        }
+    }
+
+    fn is_const(&self) -> bool {
+        matches!(*self, Expr::Const(_))
+    }
+
+    fn has_load(&self) -> bool {
+        match *self {
+            Expr::Const(_) | Expr::Symbol(_) => false,
+            Expr::Load(_) => true,
+            Expr::Add(ref a, ref b) | Expr::Mul(ref a, ref b) | Expr::LessThan(ref a, ref b) | Expr::Equals(ref a, ref b) => a.has_load() || b.has_load(),
+        }
+    }
+
+    // The value of the expression once every symbol is given one. Loads
+    // depend on memory at the time of the read, so they can't be evaluated.
+    pub fn evaluate(&self, values:&HashMap<String, i64>) -> Option<i64> {
+        match *self {
+            Expr::Const(value) => Some(value),
+            Expr::Symbol(ref name) => values.get(name).cloned(),
+            Expr::Load(_) => None,
+            Expr::Add(ref a, ref b) => a.evaluate(values)?.checked_add(b.evaluate(values)?),
+            Expr::Mul(ref a, ref b) => a.evaluate(values)?.checked_mul(b.evaluate(values)?),
+            Expr::LessThan(ref a, ref b) => Some(if a.evaluate(values)? < b.evaluate(values)? {1} else {0}),
+            Expr::Equals(ref a, ref b) => Some(if a.evaluate(values)? == b.evaluate(values)? {1} else {0}),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Expr::Const(value) => write!(f, "{}", value),
+            Expr::Symbol(ref name) => write!(f, "{}", name),
+            Expr::Load(ref address) => write!(f, "[{}]", address),
+            Expr::Add(ref a, ref b) => match **b {
+                Expr::Const(value) if value < 0 && value != i64::MIN => write!(f, "{} - {}", a, -value),
+                _ => write!(f, "{} + {}", a, b),
+            },
+            Expr::Mul(ref a, ref b) => {
+                for (i, operand) in [a, b].iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " * ")?;
+                    }
+
+                    match ***operand {
+                        Expr::Add(_, _) => write!(f, "({})", operand)?,
+                        _ => write!(f, "{}", operand)?,
+                    };
+                }
+
+                Ok(())
+            },
+            Expr::LessThan(ref a, ref b) => write!(f, "({} < {})", a, b),
+            Expr::Equals(ref a, ref b) => write!(f, "({} == {})", a, b),
+        }
+    }
+}
+
+impl Word for Expr {
+    fn from_i64(value:i64) -> Expr {
+        Expr::Const(value)
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        match *self {
+            Expr::Const(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn parse_word(token:&str) -> Option<Expr> {
+        token.parse::<i64>().ok().map(Expr::Const)
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == Expr::Const(0)
+    }
+
+    fn checked_sum(&self, other:&Expr) -> Option<Expr> {
+        Expr::sum(self, other)
+    }
+
+    fn checked_product(&self, other:&Expr) -> Option<Expr> {
+        Expr::product(self, other)
+    }
+}
+
+// The outcome of a conditional jump whose condition wasn't known: the
+// condition was non-zero on this path, or zero.
+#[derive(Clone,PartialEq,Debug)]
+pub struct Condition {
+    pub pc: usize,
+    pub expr: Expr,
+    pub nonzero: bool,
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}: {} {} 0", self.pc, self.expr, if self.nonzero { "!=" } else { "==" })
+    }
+}
+
+#[derive(PartialEq,Debug)]
+pub enum PathEnd {
+    Halted,
+    NeedsInput,
+    Error(IntcodeError),
+    // The instruction at pc needed a concrete value it only had symbolically:
+    // its opcode, a write address, a jump target or a relative base offset
+    Symbolic { pc: usize },
+    StepLimit,
+}
+
+#[derive(Clone,PartialEq,Debug)]
+pub struct SymbolicPath {
+    pub memory: Memory<Expr>,
+    pub pc: usize,
+    pub base: i64,
+    pub input: VecDeque<Expr>,
+    pub conditions: Vec<Condition>,
+    pub outputs: Vec<Expr>,
+}
+
+impl SymbolicPath {
+    // Starts at address 0 of the program with the given cells replaced by
+    // symbols and a symbol for each input.
+    pub fn new(program:&Vec<i64>, symbols:&[(usize, &str)], inputs:&[&str]) -> SymbolicPath {
+        let mut memory = Memory::from_words(program.iter().map(|&value| Expr::Const(value)).collect());
+
+        for &(address, name) in symbols {
+            memory.write(address, Expr::symbol(name));
+        }
+
+        SymbolicPath {
+            memory: memory,
+            pc: 0,
+            base: 0,
+            input: inputs.iter().map(|name| Expr::symbol(name)).collect(),
+            conditions: vec![],
+            outputs: vec![],
+        }
+    }
+
+    pub fn read(&self, address:usize) -> Expr {
+        self.memory.read(address)
+    }
+
+    // The branch a condition already decided earlier on this path, if any.
+    // Conditions on loads may have changed since, so they are never reused.
+    fn decided(&self, expr:&Expr) -> Option<bool> {
+        if expr.has_load() {
+            return None;
+        }
+
+        self.conditions.iter().find(|condition| condition.expr == *expr).map(|condition| condition.nonzero)
+    }
+}
+
+// Like read_parameter, but a symbolic address gives a Load instead of failing.
+fn read_operand(pc:usize, mode:&ParameterMode, value:&Expr, memory:&Memory<Expr>, base:i64) -> Result<Expr, IntcodeError> {
+    if *mode == ParameterMode::Immediate || value.is_const() {
+        return read_parameter(pc, mode, value.clone(), memory, base);
+    }
+
+    let address = match *mode {
+        ParameterMode::Relative => Expr::sum(&Expr::Const(base), value),
+        _ => Some(value.clone()),
+    };
+
+    match address {
+        Some(address) => Ok(Expr::Load(Box::new(address))),
+        None => Err(IntcodeError::Overflow { pc: pc }),
+    }
+}
+
+// Executes one instruction of the path. Returns the path taking the other
+// branch when a jump on a symbolic condition splits it in two.
+fn step(path:&mut SymbolicPath) -> Result<Option<SymbolicPath>, PathEnd> {
+    let pc = path.pc;
+
+    let instruction = match get_instruction_at_index(pc, &path.memory) {
+        Ok(instruction) => instruction,
+        Err(IntcodeError::Overflow { .. }) => return Err(PathEnd::Symbolic { pc: pc }),
+        Err(error) => return Err(PathEnd::Error(error)),
+    };
+
+    let code = instruction.code;
+    let size = instruction.size;
+
+    if code == 99 {
+        return Err(PathEnd::Halted);
+    }
+
+    if code == 3 && path.input.is_empty() {
+        return Err(PathEnd::NeedsInput);
+    }
+
+    let mut operands = vec![];
+    let mut concrete_addresses = true;
+    let write_parameter = get_write_parameter(code);
+
+    for (i, (mode, value)) in get_parameters(&instruction).into_iter().enumerate() {
+        if *mode != ParameterMode::Immediate && !value.is_const() {
+            if write_parameter == Some(i + 1) {
+                return Err(PathEnd::Symbolic { pc: pc });
+            }
+
+            concrete_addresses = false;
+        }
+
+        if write_parameter != Some(i + 1) {
+            operands.push(read_operand(pc, mode, &value, &path.memory, path.base).map_err(PathEnd::Error)?);
+        }
+    }
+
+    // Anything the machine itself can run is left to execute_instruction:
+    // arithmetic works on expressions, but comparisons, jumps and base
+    // adjustments need concrete operands
+    if concrete_addresses && ([1,2,3,4].contains(&code) || operands.iter().all(Expr::is_const)) {
+        let input = if code == 3 { path.input.pop_front() } else { None };
+
+        let (jump_address, relative_base, output) = execute_instruction(pc, instruction, &mut path.memory, path.base, input).map_err(PathEnd::Error)?;
+
+        if let Some(offset) = relative_base {
+            path.base = path.base.checked_add(offset).ok_or(PathEnd::Error(IntcodeError::Overflow { pc: pc }))?;
+        }
+
+        if let Some(output) = output {
+            path.outputs.push(output);
+        }
+
+        path.pc = jump_address.unwrap_or(pc + size);
+
+        return Ok(None);
+    }
+
+    let value = match code {
+        1 => Expr::sum(&operands[0], &operands[1]),
+        2 => Expr::product(&operands[0], &operands[1]),
+        7 => Some(Expr::less_than(&operands[0], &operands[1])),
+        8 => Some(Expr::equals(&operands[0], &operands[1])),
+        4 => {
+            path.outputs.push(operands[0].clone());
+            path.pc = pc + size;
+
+            return Ok(None);
+        },
+        5 | 6 => return branch(path, code, &operands[0], &operands[1]),
+        _ => return Err(PathEnd::Symbolic { pc: pc }),
+    };
+
+    let value = value.ok_or(PathEnd::Error(IntcodeError::Overflow { pc: pc }))?;
+    let address = get_write_address(pc, &instruction.p3_mode, instruction.p3, path.base).map_err(PathEnd::Error)?;
+
+    path.memory.write(address, value);
+    path.pc = pc + size;
+
+    return Ok(None);
+}
+
+fn branch(path:&mut SymbolicPath, code:i64, condition:&Expr, target:&Expr) -> Result<Option<SymbolicPath>, PathEnd> {
+    let pc = path.pc;
+
+    let target = match target.to_i64() {
+        Some(target) if target >= 0 => target as usize,
+        Some(target) => return Err(PathEnd::Error(IntcodeError::NegativeAddress { pc: pc, address: target })),
+        None => return Err(PathEnd::Symbolic { pc: pc }),
+    };
+
+    let jump_when = code == 5;
+
+    let nonzero = match condition.to_i64() {
+        Some(value) => Some(value != 0),
+        None => path.decided(condition),
+    };
+
+    if let Some(nonzero) = nonzero {
+        path.pc = if nonzero == jump_when { target } else { pc + 3 };
+        return Ok(None);
+    }
+
+    let mut other = path.clone();
+
+    path.conditions.push(Condition { pc: pc, expr: condition.clone(), nonzero: jump_when });
+    path.pc = target;
+
+    other.conditions.push(Condition { pc: pc, expr: condition.clone(), nonzero: !jump_when });
+    other.pc = pc + 3;
+
+    return Ok(Some(other));
+}
+
+// Runs every path through the program until it ends, for at most max_steps
+// instructions in total. Paths take turns so that one stuck in a loop
+// doesn't use up the budget of the others; they are returned in the order
+// they ended, along with why.
+pub fn execute_symbolic(path:SymbolicPath, max_steps:usize) -> Vec<(SymbolicPath, PathEnd)> {
+    let mut pending = VecDeque::new();
+    let mut finished = vec![];
+    let mut steps = 0;
+
+    pending.push_back(path);
+
+    while let Some(mut path) = pending.pop_front() {
+        if steps >= max_steps {
+            finished.push((path, PathEnd::StepLimit));
+            continue;
+        }
+
+        steps = steps + 1;
+
+        match step(&mut path) {
+            Ok(None) => pending.push_back(path),
+            Ok(Some(other)) => {
+                pending.push_back(path);
+                pending.push_back(other);
+            },
+            Err(end) => finished.push((path, end)),
+        }
+    }
+
+    return finished;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use machine::Intcode;
+
+    // Day 2 style: [3] = [noun] + [verb], then overwritten with noun + verb,
+    // then [0] = [3] * [13]
+    fn noun_verb_program() -> Vec<i64> {
+        vec![1,0,0,3,1,1,2,3,2,3,13,0,99,5]
+    }
+
+    #[test]
+    fn test_simplification() {
+        let x = Expr::symbol("x");
+        let y = Expr::symbol("y");
+
+        let sum = Expr::sum(&Expr::sum(&x, &Expr::Const(3)).unwrap(), &Expr::Const(4)).unwrap();
+
+        assert_eq!(sum.to_string(), "x + 7");
+
+        let scaled = Expr::product(&Expr::sum(&sum, &y).unwrap(), &Expr::Const(2)).unwrap();
+
+        assert_eq!(scaled.to_string(), "x * 2 + y * 2 + 14");
+        assert_eq!(Expr::product(&Expr::Const(0), &x).unwrap(), Expr::Const(0));
+        assert_eq!(Expr::sum(&x, &Expr::Const(-1)).unwrap().to_string(), "x - 1");
+        assert_eq!(Expr::product(&Expr::sum(&x, &y).unwrap(), &x).unwrap().to_string(), "(x + y) * x");
+        assert_eq!(Expr::equals(&x, &x), Expr::Const(1));
+        assert_eq!(Expr::sum(&Expr::Const(i64::MAX), &Expr::Const(1)), None);
+    }
+
+    #[test]
+    fn test_noun_verb_formula() {
+        let path = SymbolicPath::new(&noun_verb_program(), &[(1, "noun"), (2, "verb")], &[]);
+        let paths = execute_symbolic(path, 100);
+
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].1, PathEnd::Halted);
+
+        let formula = paths[0].0.read(0);
+
+        assert_eq!(formula.to_string(), "noun * 5 + verb * 5");
+
+        // The formula agrees with running the patched program
+        let values: HashMap<String, i64> = vec![("noun".to_string(), 3), ("verb".to_string(), 4)].into_iter().collect();
+
+        let mut program = noun_verb_program();
+        program[1] = 3;
+        program[2] = 4;
+
+        let mut machine = Intcode::new(program);
+        machine.run(&mut vec![], &mut vec![]).unwrap();
+
+        assert_eq!(formula.evaluate(&values), Some(machine.read(0)));
+    }
+
+    #[test]
+    fn test_symbolic_address_read() {
+        let path = SymbolicPath::new(&vec![1,0,0,3,99], &[(1, "noun"), (2, "verb")], &[]);
+        let paths = execute_symbolic(path, 100);
+
+        assert_eq!(paths[0].0.read(3).to_string(), "[noun] + [verb]");
+        assert_eq!(paths[0].0.read(3).evaluate(&HashMap::new()), None);
+    }
+
+    #[test]
+    fn test_path_conditions() {
+        // Outputs 1 if the input is less than 5, and 0 otherwise
+        let program = vec![3,20,1007,20,5,21,1005,21,12,104,0,99,104,1,99];
+        let paths = execute_symbolic(SymbolicPath::new(&program, &[], &["x"]), 100);
+
+        assert_eq!(paths.len(), 2);
+
+        let conditions: Vec<String> = paths.iter().map(|&(ref path, _)| path.conditions[0].to_string()).collect();
+        let outputs: Vec<Vec<Expr>> = paths.iter().map(|&(ref path, _)| path.outputs.clone()).collect();
+
+        assert_eq!(conditions, vec!["0006: (x < 5) != 0", "0006: (x < 5) == 0"]);
+        assert_eq!(outputs, vec![vec![Expr::Const(1)], vec![Expr::Const(0)]]);
+        assert!(paths.iter().all(|&(_, ref end)| *end == PathEnd::Halted));
+    }
+
+    #[test]
+    fn test_decided_conditions_are_not_split_again() {
+        // Jumps back to 2 for as long as the input is non-zero
+        let paths = execute_symbolic(SymbolicPath::new(&vec![3,10,1005,10,2,99], &[], &["x"]), 100);
+
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].1, PathEnd::Halted);
+        assert_eq!(paths[0].0.conditions, vec![Condition { pc: 2, expr: Expr::symbol("x"), nonzero: false }]);
+        assert_eq!(paths[1].1, PathEnd::StepLimit);
+        assert_eq!(paths[1].0.conditions.len(), 1);
+    }
+
+    #[test]
+    fn test_loads_across_writes() {
+        // [20] = [ptr], [30] = 7, [21] = [ptr], outputs [20] == [21]
+        let mut program = vec![1001,0,0,20,1101,7,0,30,1001,0,0,21,8,20,21,22,4,22,99];
+        program.resize(31, 0);
+
+        let paths = execute_symbolic(SymbolicPath::new(&program, &[(1, "ptr"), (9, "ptr")], &[]), 100);
+
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].0.outputs[0].to_string(), "([ptr] == [ptr])");
+
+        // Pointing at the written cell, the two reads really do differ
+        program[1] = 30;
+        program[9] = 30;
+
+        let mut machine = Intcode::new(program);
+        let mut outputs = vec![];
+        machine.run(&mut vec![], &mut outputs).unwrap();
+
+        assert_eq!(outputs, vec![0]);
+
+        // A branch on [ptr] is split again after the write
+        let mut program = vec![1005,0,3,1101,7,0,30,1005,0,10,99];
+        program.resize(31, 0);
+
+        let paths = execute_symbolic(SymbolicPath::new(&program, &[(1, "ptr"), (8, "ptr")], &[]), 100);
+
+        assert_eq!(paths.len(), 4);
+        assert!(paths.iter().all(|&(ref path, ref end)| *end == PathEnd::Halted && path.conditions.len() == 2));
+    }
+
+    #[test]
+    fn test_unresolvable_paths() {
+        // Writes through an address read from input
+        let paths = execute_symbolic(SymbolicPath::new(&vec![3,5,1101,1,1,0,99], &[], &["x"]), 100);
+
+        assert_eq!(paths[0].1, PathEnd::Symbolic { pc: 2 });
+
+        let paths = execute_symbolic(SymbolicPath::new(&vec![3,0,99], &[], &[]), 100);
+
+        assert_eq!(paths[0].1, PathEnd::NeedsInput);
+
+        let paths = execute_symbolic(SymbolicPath::new(&vec![42], &[], &[]), 100);
+
+        assert_eq!(paths[0].1, PathEnd::Error(IntcodeError::InvalidOpcode { pc: 0, opcode: 42 }));
+    }
+}
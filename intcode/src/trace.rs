@@ -0,0 +1,297 @@
+use std::convert::TryFrom;
+use std::io::Write;
+
+use disassemble::get_mnemonic;
+use error::IntcodeError;
+use instruction::{encode_instruction_code, get_parameters, parse_instruction_code, Instruction};
+use json::{parse_json, Json};
+use machine::{Intcode, RunState};
+
+#[derive(PartialEq,Debug)]
+pub struct TraceEvent {
+    pub step: u64,
+    pub pc: usize,
+    pub instruction: Instruction,
+    pub operands: Vec<i64>,
+    pub write: Option<(usize, i64)>,
+    pub base: Option<i64>,
+    pub input: Option<i64>,
+    pub output: Option<i64>,
+}
+
+// Only one is produced per replay, so the size of a divergence doesn't matter
+#[allow(clippy::large_enum_variant)]
+#[derive(PartialEq,Debug)]
+pub enum ReplayResult {
+    Match { steps: u64 },
+    Divergence { step: u64, expected: TraceEvent, actual: Option<TraceEvent> },
+}
+
+fn optional_number(value:Option<i64>) -> Json {
+    match value {
+        Some(n) => Json::Number(n),
+        None => Json::Null,
+    }
+}
+
+impl TraceEvent {
+    pub fn to_json(&self) -> Json {
+        let params: Vec<Json> = get_parameters(&self.instruction).iter().map(|&(_, value)| Json::Number(value)).collect();
+
+        Json::Object(vec![
+            ("step".to_string(), Json::Number(self.step as i64)),
+            ("pc".to_string(), Json::Number(self.pc as i64)),
+            ("op".to_string(), Json::String(get_mnemonic(self.instruction.code).unwrap_or("???").to_string())),
+            ("code".to_string(), Json::Number(encode_instruction_code(&self.instruction))),
+            ("params".to_string(), Json::Array(params)),
+            ("operands".to_string(), Json::Array(self.operands.iter().map(|&n| Json::Number(n)).collect())),
+            ("write".to_string(), match self.write {
+                Some((address, value)) => Json::Array(vec![Json::Number(address as i64), Json::Number(value)]),
+                None => Json::Null,
+            }),
+            ("base".to_string(), optional_number(self.base)),
+            ("input".to_string(), optional_number(self.input)),
+            ("output".to_string(), optional_number(self.output)),
+        ])
+    }
+
+    pub fn from_json(value:&Json) -> Option<TraceEvent> {
+        let number = |key:&str| value.get(key).and_then(|v| v.as_i64());
+        let numbers = |key:&str| -> Option<Vec<i64>> {
+            value.get(key)?.as_array()?.iter().map(|v| v.as_i64()).collect()
+        };
+
+        let (p1_mode, p2_mode, p3_mode, code) = parse_instruction_code(number("code")?).ok()?;
+        let params = numbers("params")?;
+
+        let write = match value.get("write")? {
+            &Json::Null => None,
+            w => {
+                let pair: Vec<i64> = w.as_array()?.iter().map(|v| v.as_i64()).collect::<Option<Vec<i64>>>()?;
+
+                Some((usize::try_from(*pair.first()?).ok()?, *pair.get(1)?))
+            },
+        };
+
+        Some(TraceEvent {
+            step: u64::try_from(number("step")?).ok()?,
+            pc: usize::try_from(number("pc")?).ok()?,
+            instruction: Instruction {
+                code: code,
+                p1_mode: p1_mode,
+                p2_mode: p2_mode,
+                p3_mode: p3_mode,
+                p1: *params.first().unwrap_or(&0),
+                p2: *params.get(1).unwrap_or(&0),
+                p3: *params.get(2).unwrap_or(&0),
+                size: params.len() + 1,
+            },
+            operands: numbers("operands")?,
+            write: write,
+            base: number("base"),
+            input: number("input"),
+            output: number("output"),
+        })
+    }
+}
+
+// Executes one instruction, also describing what it did. No event is
+// produced when nothing was executed (halt or waiting for input).
+pub fn step_with_trace(machine:&mut Intcode, step:u64) -> Result<(Option<RunState>, Option<TraceEvent>), IntcodeError> {
+    let pending = match machine.peek_instruction()? {
+        Some(pending) => pending,
+        None => return Ok((machine.step()?, None)),
+    };
+
+    let operands = pending.parameters.iter().filter(|parameter| !parameter.write).map(|parameter| match parameter.address {
+        Some(address) => machine.read(address),
+        None => parameter.value,
+    }).collect();
+
+    let write_address = pending.write_address();
+    let input = if pending.instruction.code == 3 { machine.input.front().cloned() } else { None };
+    let base_before = machine.base;
+
+    let state = machine.step()?;
+
+    let event = TraceEvent {
+        step: step,
+        pc: pending.pc,
+        instruction: pending.instruction,
+        operands: operands,
+        write: write_address.map(|address| (address, machine.read(address))),
+        base: if machine.base != base_before { Some(machine.base) } else { None },
+        input: input,
+        output: match state {
+            Some(RunState::Output(out)) => Some(out),
+            _ => None,
+        },
+    };
+
+    return Ok((state, Some(event)));
+}
+
+pub struct TraceRecorder<W: Write> {
+    pub writer: W,
+    pub steps: u64,
+}
+
+impl<W: Write> TraceRecorder<W> {
+    pub fn new(writer:W) -> TraceRecorder<W> {
+        TraceRecorder {
+            writer: writer,
+            steps: 0,
+        }
+    }
+
+    pub fn step(&mut self, machine:&mut Intcode) -> Result<Option<RunState>, IntcodeError> {
+        let (state, event) = step_with_trace(machine, self.steps)?;
+
+        if let Some(event) = event {
+            writeln!(self.writer, "{}", event.to_json())?;
+            self.steps = self.steps + 1;
+        }
+
+        return Ok(state);
+    }
+
+    pub fn run_until_event(&mut self, machine:&mut Intcode) -> Result<RunState, IntcodeError> {
+        loop {
+            if let Some(state) = self.step(machine)? {
+                return Ok(state);
+            }
+        }
+    }
+}
+
+pub fn parse_trace(trace:&str) -> Result<Vec<TraceEvent>, IntcodeError> {
+    let mut events = vec![];
+
+    for line in trace.lines().filter(|line| !line.trim().is_empty()) {
+        let event = match parse_json(line) {
+            Ok(value) => TraceEvent::from_json(&value),
+            Err(_) => None,
+        };
+
+        match event {
+            Some(event) => events.push(event),
+            None => return Err(IntcodeError::ParseError { token: line.to_string() }),
+        };
+    }
+
+    return Ok(events);
+}
+
+// Re-runs a recorded trace against a program, feeding it the inputs the
+// trace consumed, and reports the first step where execution differs.
+pub fn replay_trace(program:Vec<i64>, trace:&str) -> Result<ReplayResult, IntcodeError> {
+    let events = parse_trace(trace)?;
+    let mut machine = Intcode::new(program);
+
+    for input in events.iter().filter_map(|event| event.input) {
+        machine.push_input(input);
+    }
+
+    let mut steps = 0;
+
+    for expected in events {
+        let actual = match step_with_trace(&mut machine, steps) {
+            Ok((_, event)) => event,
+            Err(_) => None,
+        };
+
+        if actual.as_ref() != Some(&expected) {
+            return Ok(ReplayResult::Divergence { step: steps, expected: expected, actual: actual });
+        }
+
+        steps = steps + 1;
+    }
+
+    return Ok(ReplayResult::Match { steps: steps });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(program:Vec<i64>, inputs:Vec<i64>) -> String {
+        let mut machine = Intcode::new(program);
+        let mut recorder = TraceRecorder::new(vec![]);
+
+        for input in inputs {
+            machine.push_input(input);
+        }
+
+        while recorder.run_until_event(&mut machine).unwrap() != RunState::Halted {}
+
+        return String::from_utf8(recorder.writer).unwrap();
+    }
+
+    #[test]
+    fn test_record_trace() {
+        let trace = record(vec![109,10,203,1,204,1,99], vec![42]);
+        let lines: Vec<&str> = trace.lines().collect();
+
+        assert_eq!(lines, vec![
+            "{\"step\":0,\"pc\":0,\"op\":\"ARB\",\"code\":109,\"params\":[10],\"operands\":[10],\"write\":null,\"base\":10,\"input\":null,\"output\":null}",
+            "{\"step\":1,\"pc\":2,\"op\":\"IN\",\"code\":203,\"params\":[1],\"operands\":[],\"write\":[11,42],\"base\":null,\"input\":42,\"output\":null}",
+            "{\"step\":2,\"pc\":4,\"op\":\"OUT\",\"code\":204,\"params\":[1],\"operands\":[42],\"write\":null,\"base\":null,\"input\":null,\"output\":42}",
+        ]);
+    }
+
+    #[test]
+    fn test_parse_trace_round_trip() {
+        let program = vec![3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99];
+        let trace = record(program, vec![8]);
+        let events = parse_trace(&trace).unwrap();
+
+        let lines: Vec<String> = events.iter().map(|event| event.to_json().to_string()).collect();
+
+        assert_eq!(lines.join("\n") + "\n", trace);
+    }
+
+    #[test]
+    fn test_replay_match() {
+        let program = vec![3,9,8,9,10,9,4,9,99,-1,8];
+        let trace = record(program.clone(), vec![8]);
+
+        assert_eq!(replay_trace(program, &trace).unwrap(), ReplayResult::Match { steps: 3 });
+    }
+
+    #[test]
+    fn test_replay_divergence() {
+        let trace = record(vec![3,9,8,9,10,9,4,9,99,-1,8], vec![8]);
+
+        // Less-than instead of equals: the second instruction writes 0 rather than 1
+        match replay_trace(vec![3,9,7,9,10,9,4,9,99,-1,8], &trace).unwrap() {
+            ReplayResult::Divergence { step, expected, actual } => {
+                assert_eq!(step, 1);
+                assert_eq!(expected.write, Some((9, 1)));
+                assert_eq!(actual.unwrap().write, Some((9, 0)));
+            },
+            result => panic!("expected divergence, got {:?}", result),
+        };
+
+        // Program halts before the trace ends
+        match replay_trace(vec![3,9,99], &trace).unwrap() {
+            ReplayResult::Divergence { step, actual, .. } => {
+                assert_eq!(step, 1);
+                assert_eq!(actual, None);
+            },
+            result => panic!("expected divergence, got {:?}", result),
+        };
+    }
+
+    #[test]
+    fn test_parse_trace_error() {
+        assert_eq!(parse_trace("{\"step\":0}"), Err(IntcodeError::ParseError { token: "{\"step\":0}".to_string() }));
+        // Negative addresses don't wrap around to huge ones
+        let line = "{\"step\":1,\"pc\":2,\"op\":\"IN\",\"code\":203,\"params\":[1],\"operands\":[],\"write\":[11,42],\"base\":null,\"input\":42,\"output\":null}";
+
+        assert!(parse_trace(line).is_ok());
+
+        for bad in [line.replace("\"pc\":2", "\"pc\":-1"), line.replace("[11,42]", "[-1,42]"), line.replace("\"step\":1", "\"step\":-1")].iter() {
+            assert_eq!(parse_trace(bad), Err(IntcodeError::ParseError { token: bad.to_string() }));
+        }
+    }
+}
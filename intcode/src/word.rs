@@ -0,0 +1,66 @@
+use std::fmt::{Debug, Display};
+
+use bigint::BigInt;
+
+// The value type stored in Intcode memory. Arithmetic returns None when the
+// result can't be represented, which the machine reports as an overflow.
+pub trait Word: Clone + PartialEq + PartialOrd + Debug + Display {
+    fn from_i64(value:i64) -> Self;
+    fn to_i64(&self) -> Option<i64>;
+    fn parse_word(token:&str) -> Option<Self>;
+    fn is_zero(&self) -> bool;
+    fn checked_sum(&self, other:&Self) -> Option<Self>;
+    fn checked_product(&self, other:&Self) -> Option<Self>;
+}
+
+impl Word for i64 {
+    fn from_i64(value:i64) -> i64 {
+        value
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        Some(*self)
+    }
+
+    fn parse_word(token:&str) -> Option<i64> {
+        token.parse::<i64>().ok()
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0
+    }
+
+    fn checked_sum(&self, other:&i64) -> Option<i64> {
+        self.checked_add(*other)
+    }
+
+    fn checked_product(&self, other:&i64) -> Option<i64> {
+        self.checked_mul(*other)
+    }
+}
+
+impl Word for BigInt {
+    fn from_i64(value:i64) -> BigInt {
+        BigInt::from_i64(value)
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        BigInt::to_i64(self)
+    }
+
+    fn parse_word(token:&str) -> Option<BigInt> {
+        BigInt::parse(token)
+    }
+
+    fn is_zero(&self) -> bool {
+        BigInt::is_zero(self)
+    }
+
+    fn checked_sum(&self, other:&BigInt) -> Option<BigInt> {
+        Some(self.add(other))
+    }
+
+    fn checked_product(&self, other:&BigInt) -> Option<BigInt> {
+        Some(self.mul(other))
+    }
+}
@@ -0,0 +1,159 @@
+extern crate aoc;
+extern crate year2018;
+extern crate year2019;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+
+use aoc::{check_answer, format_answer, format_verdict, parse_run_args, parse_verify_args, run_part, Solution, Store, Verdict};
+
+// Every solved day, in the order they're verified
+fn get_solutions() -> Vec<(u32, u32, Box<dyn Solution>)> {
+    vec![
+        (2018, 1, Box::new(year2018::day01::Day)),
+        (2019, 1, Box::new(year2019::day01::Day)),
+        (2019, 2, Box::new(year2019::day02::Day)),
+        (2019, 3, Box::new(year2019::day03::Day)),
+        (2019, 4, Box::new(year2019::day04::Day)),
+        (2019, 5, Box::new(year2019::day05::Day)),
+        (2019, 6, Box::new(year2019::day06::Day)),
+        (2019, 7, Box::new(year2019::day07::Day)),
+        (2019, 8, Box::new(year2019::day08::Day)),
+        (2019, 9, Box::new(year2019::day09::Day)),
+    ]
+}
+
+fn get_solution(year:u32, day:u32) -> Option<Box<dyn Solution>> {
+    get_solutions().into_iter().find(|&(y, d, _)| y == year && d == day).map(|(_, _, solution)| solution)
+}
+
+fn usage(program:&str) -> io::Error {
+    println!("usage: {} run --year YEAR --day DAY [--part 1|2] [--input path]", program);
+    println!("       {} verify [--year YEAR] [--day DAY]", program);
+    println!();
+    println!("Inputs and recorded answers are read from inputs/YEAR/DD.txt and");
+    println!("answers/YEAR/DD.toml under the current directory.");
+
+    return io::Error::new(io::ErrorKind::InvalidInput, "invalid arguments");
+}
+
+fn run(program:&str, args:&[String], store:&Store) -> io::Result<()> {
+    let options = match parse_run_args(args) {
+        Ok(options) => options,
+        Err(error) => {
+            println!("error: {}", error);
+            return Err(usage(program));
+        },
+    };
+
+    let solution = match get_solution(options.year, options.day) {
+        Some(solution) => solution,
+        None => return Err(io::Error::new(io::ErrorKind::NotFound, format!("no solution for {} day {}", options.year, options.day))),
+    };
+
+    let input = match options.input {
+        Some(ref path) => fs::read_to_string(path)?,
+        None => match store.load_input(options.year, options.day)? {
+            Some(input) => input,
+            None => {
+                let mut input = String::new();
+                io::stdin().read_to_string(&mut input)?;
+                input
+            },
+        },
+    };
+
+    let answers = store.load_answers(options.year, options.day)?;
+
+    println!("{} day {}", options.year, options.day);
+
+    let parts = match options.part {
+        Some(part) => vec![part],
+        None => vec![1, 2],
+    };
+
+    for part in parts {
+        let (answer, duration) = run_part(solution.as_ref(), part, &input);
+
+        println!("{}", format_answer(part, &answer, duration));
+
+        // Only answers given for the stored input can be checked against it
+        if options.input.is_none() {
+            if let verdict @ Verdict::Wrong { .. } = check_answer(&answer, answers.get(part)) {
+                println!("part {}: {}", part, verdict);
+            }
+        }
+    }
+
+    for note in solution.notes(&input) {
+        println!("{}", note);
+    }
+
+    Ok(())
+}
+
+// Runs every solved day with a stored input and compares both parts with the
+// recorded answers. Fails if any answer is wrong or any part fails.
+fn verify(program:&str, args:&[String], store:&Store) -> io::Result<()> {
+    let options = match parse_verify_args(args) {
+        Ok(options) => options,
+        Err(error) => {
+            println!("error: {}", error);
+            return Err(usage(program));
+        },
+    };
+
+    let (mut correct, mut failures, mut unrecorded, mut missing) = (0, 0, 0, 0);
+
+    for (year, day, solution) in get_solutions() {
+        if options.year.is_some_and(|y| y != year) || options.day.is_some_and(|d| d != day) {
+            continue;
+        }
+
+        let input = match store.load_input(year, day)? {
+            Some(input) => input,
+            None => {
+                println!("{} day {}: no input at {}", year, day, store.input_path(year, day).display());
+                missing = missing + 1;
+                continue;
+            },
+        };
+
+        let answers = store.load_answers(year, day)?;
+
+        for part in 1..3 {
+            let (answer, duration) = run_part(solution.as_ref(), part, &input);
+            let verdict = check_answer(&answer, answers.get(part));
+
+            println!("{}", format_verdict(year, day, part, &verdict, duration));
+
+            match verdict {
+                Verdict::Correct => correct = correct + 1,
+                Verdict::Unrecorded(_) => unrecorded = unrecorded + 1,
+                Verdict::Wrong { .. } | Verdict::Failed(_) => failures = failures + 1,
+            }
+        }
+    }
+
+    println!();
+    println!("{} correct, {} failed, {} not recorded, {} days without input", correct, failures, unrecorded, missing);
+
+    if failures > 0 {
+        return Err(io::Error::other(format!("{} of {} parts failed", failures, correct + failures + unrecorded)));
+    }
+
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+    let store = Store::new(env::current_dir()?);
+
+    match args.get(1).map(|command| command.as_str()) {
+        Some("run") => run(&args[0], &args[2..], &store),
+        Some("verify") => verify(&args[0], &args[2..], &store),
+        _ => Err(usage(&args[0])),
+    }
+}